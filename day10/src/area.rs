@@ -0,0 +1,48 @@
+/*
+The loop is a lattice polygon whose vertices are the centres of its tiles,
+so two classic results count the enclosed tiles without touching the board:
+
+    shoelace:  2A = |sum over edges of (x1 * y2 - x2 * y1)|
+    Pick:      A = i + b/2 - 1
+
+where b is the number of lattice points on the boundary (every loop tile)
+and i the number strictly inside, which are exactly the enclosed tiles.
+*/
+
+/// Twice the area of the polygon through `path`, taken in order and closed
+/// back to the first point. Doubling keeps it an integer.
+pub fn shoelace(path: &[(i32, i32)]) -> i64 {
+    let mut sum: i64 = 0;
+    for (idx, (x1, y1)) in path.iter().enumerate() {
+        let (x2, y2) = path[(idx + 1) % path.len()];
+        sum += *x1 as i64 * y2 as i64 - x2 as i64 * *y1 as i64;
+    }
+    sum.abs()
+}
+
+/// Tiles enclosed by the loop `path`, which visits every loop tile once.
+pub fn pick_interior(path: &[(i32, i32)]) -> usize {
+    // i = A - b/2 + 1, doubled through to stay in integers
+    let boundary = path.len() as i64;
+    ((shoelace(path) - boundary + 2) / 2) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flood_interior;
+    use crate::tests::solved;
+
+    #[test]
+    fn pick_matches_flood() {
+        for (input, enclosed) in [
+            (include_str!("../test.txt"), 1),
+            (include_str!("../test2.txt"), 8),
+            (include_str!("../test3.txt"), 10),
+        ] {
+            let (start_loc, board, path) = solved(input);
+            assert_eq!(pick_interior(&path), enclosed);
+            assert_eq!(flood_interior(start_loc, &board, &path), enclosed);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use colored::Colorize;
+
+use crate::grid::{Grid, Tiles};
+use crate::Board;
+
+/// How far a loop tile is from S, going each way round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopDistance {
+    /// steps following the loop in the order it was walked
+    pub forward: usize,
+    /// steps going the other way
+    pub backward: usize,
+}
+
+impl LoopDistance {
+    pub fn shortest(&self) -> usize {
+        self.forward.min(self.backward)
+    }
+}
+
+/// Distances from S for every tile on the loop `path`, None for the rest.
+pub fn distance_map(board: &Board, path: &[(i32, i32)]) -> Grid<Option<LoopDistance>> {
+    let mut map = Grid::new(board.min_xy(), board.width(), board.height(), |_, _| None);
+    for (idx, xy) in path.iter().enumerate() {
+        *map.cell_mut(*xy).unwrap() = Some(LoopDistance {
+            forward: idx,
+            backward: (path.len() - idx) % path.len(),
+        });
+    }
+    map
+}
+
+/// The largest shortest distance from S and every tile that far away.
+/// On an even loop that's the one tile opposite S, on an odd one (which
+/// pipes can't make, but the map doesn't know that) the two either side.
+pub fn farthest(map: &Grid<Option<LoopDistance>>) -> (usize, Vec<(i32, i32)>) {
+    let max = map.iter().filter_map(|(_, d)| d.map(|d| d.shortest())).max().unwrap_or(0);
+    let tiles = map.iter()
+        .filter(|(_, d)| d.is_some_and(|d| d.shortest() == max))
+        .map(|(xy, _)| xy)
+        .collect();
+    (max, tiles)
+}
+
+/// The board with every loop tile replaced by its shortest distance from S,
+/// the farthest ones highlighted, and everything else as its pipe.
+pub fn render(board: &Board, map: &Grid<Option<LoopDistance>>) -> String {
+    let (max, _) = farthest(map);
+    let width = max.to_string().len();
+
+    let mut out = String::new();
+    for ((_, row), (_, distances)) in board.rows().zip(map.rows()) {
+        let cells: Vec<String> = row.iter()
+            .zip(distances.iter())
+            .map(|(loc, d)| match d {
+                Some(d) if d.shortest() == max => format!("{:>width$}", d.shortest()).red().bold().to_string(),
+                Some(d) => format!("{:>width$}", d.shortest()).green().to_string(),
+                None => format!("{:>width$}", loc.piece.to_str()),
+            })
+            .collect();
+        out.push_str(&cells.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::solved;
+
+    #[test]
+    fn around_the_test_loop() {
+        let (start_loc, board, path) = solved(include_str!("../test.txt"));
+        let map = distance_map(&board, &path);
+        let at = |xy: (i32, i32)| *map.cell(xy).unwrap();
+
+        assert_eq!(at(start_loc), Some(LoopDistance { forward: 0, backward: 0 }));
+        // the walk leaves S southwards and comes back from the east
+        assert_eq!(at((0, 3)), Some(LoopDistance { forward: 1, backward: 15 }));
+        assert_eq!(at((1, 2)), Some(LoopDistance { forward: 15, backward: 1 }));
+        assert_eq!(at((4, 2)), Some(LoopDistance { forward: 8, backward: 8 }));
+        assert_eq!(at((2, 2)), None);
+
+        assert_eq!(farthest(&map), (8, vec![(4, 2)]));
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::{Direction, Location};
+
+/// Storage for a board of locations. `Grid` is what the puzzle runs on, the
+/// HashMap is the original storage, kept so `bench` can compare the two.
+pub trait Tiles {
+    /// Every position from `min` to `max` inclusive, filled in by `fill`.
+    fn filled(min: (i32, i32), max: (i32, i32), fill: impl FnMut(i32, i32) -> Location) -> Self;
+    fn get(&self, xy: &(i32, i32)) -> Option<&Location>;
+    fn get_mut(&mut self, xy: &(i32, i32)) -> Option<&mut Location>;
+    fn min_xy(&self) -> (i32, i32);
+    fn max_xy(&self) -> (i32, i32);
+    fn locations(&self) -> impl Iterator<Item = &Location>;
+
+    /// The location one step from `xy` towards `dir`, None off the edge.
+    fn neighbour(&self, xy: (i32, i32), dir: &Direction) -> Option<&Location> {
+        let (dx, dy) = dir.xy_delta();
+        self.get(&(xy.0 + dx, xy.1 + dy))
+    }
+}
+
+/// A dense rectangle of cells stored row by row, addressed by (x, y) from
+/// `min` to `min + (width, height) - 1`.
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    min: (i32, i32),
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(min: (i32, i32), width: usize, height: usize, mut fill: impl FnMut(i32, i32) -> T) -> Grid<T> {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                cells.push(fill(min.0 + x, min.1 + y));
+            }
+        }
+        Grid { min, width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn offset(&self, (x, y): (i32, i32)) -> Option<usize> {
+        let col = usize::try_from(x - self.min.0).ok().filter(|c| *c < self.width)?;
+        let row = usize::try_from(y - self.min.1).ok().filter(|r| *r < self.height)?;
+        Some(row * self.width + col)
+    }
+
+    pub fn cell(&self, xy: (i32, i32)) -> Option<&T> {
+        self.offset(xy).map(|idx| &self.cells[idx])
+    }
+
+    pub fn cell_mut(&mut self, xy: (i32, i32)) -> Option<&mut T> {
+        self.offset(xy).map(|idx| &mut self.cells[idx])
+    }
+
+    /// Every cell with its (x, y), row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), &T)> {
+        let (min, width) = (self.min, self.width);
+        self.cells.iter()
+            .enumerate()
+            .map(move |(idx, c)| ((min.0 + (idx % width) as i32, min.1 + (idx / width) as i32), c))
+    }
+
+    /// Each row with its y, top to bottom, the cells in it running from
+    /// the left edge.
+    pub fn rows(&self) -> impl Iterator<Item = (i32, &[T])> {
+        (self.min.1..).zip(self.cells.chunks(self.width.max(1)))
+    }
+}
+
+impl Tiles for Grid<Location> {
+    fn filled(min: (i32, i32), max: (i32, i32), fill: impl FnMut(i32, i32) -> Location) -> Self {
+        let width = (max.0 - min.0 + 1).max(0) as usize;
+        let height = (max.1 - min.1 + 1).max(0) as usize;
+        Grid::new(min, width, height, fill)
+    }
+
+    fn get(&self, xy: &(i32, i32)) -> Option<&Location> {
+        self.cell(*xy)
+    }
+
+    fn get_mut(&mut self, xy: &(i32, i32)) -> Option<&mut Location> {
+        self.cell_mut(*xy)
+    }
+
+    fn min_xy(&self) -> (i32, i32) {
+        self.min
+    }
+
+    fn max_xy(&self) -> (i32, i32) {
+        (self.min.0 + self.width as i32 - 1, self.min.1 + self.height as i32 - 1)
+    }
+
+    fn locations(&self) -> impl Iterator<Item = &Location> {
+        self.cells.iter()
+    }
+}
+
+impl Tiles for HashMap<(i32, i32), Location> {
+    fn filled(min: (i32, i32), max: (i32, i32), mut fill: impl FnMut(i32, i32) -> Location) -> Self {
+        let mut board = HashMap::new();
+        for y in min.1..max.1 + 1 {
+            for x in min.0..max.0 + 1 {
+                board.insert((x, y), fill(x, y));
+            }
+        }
+        board
+    }
+
+    fn get(&self, xy: &(i32, i32)) -> Option<&Location> {
+        HashMap::get(self, xy)
+    }
+
+    fn get_mut(&mut self, xy: &(i32, i32)) -> Option<&mut Location> {
+        HashMap::get_mut(self, xy)
+    }
+
+    // these rescan every key, which is most of why the grid exists
+    fn min_xy(&self) -> (i32, i32) {
+        let mut min_x = 0;
+        let mut min_y = 0;
+        for (kx, ky) in self.keys() {
+            min_x = i32::min(min_x, *kx);
+            min_y = i32::min(min_y, *ky);
+        }
+        (min_x, min_y)
+    }
+
+    fn max_xy(&self) -> (i32, i32) {
+        let mut max_x = 0;
+        let mut max_y = 0;
+        for (kx, ky) in self.keys() {
+            max_x = i32::max(max_x, *kx);
+            max_y = i32::max(max_y, *ky);
+        }
+        (max_x, max_y)
+    }
+
+    fn locations(&self) -> impl Iterator<Item = &Location> {
+        self.values()
+    }
+}
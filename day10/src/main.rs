@@ -1,18 +1,19 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::ops::Index;
+use std::time::Instant;
 use colored::Colorize;
 
-fn num_str_to_vec(input: &str) -> Vec<i64> {
-    let mut r = Vec::new();
-    for e in input.split(" ") {
-        if e.len() == 0 {
-            continue;
-        }
-        r.push(e.trim().parse().expect("a number"));
-    }
-    return r;
-}
+use grid::{Grid, Tiles};
+
+mod area;
+mod distance;
+mod grid;
+mod render;
+mod scanline;
+
+type Board = Grid<Location>;
 
 /*
 
@@ -27,7 +28,7 @@ fn num_str_to_vec(input: &str) -> Vec<i64> {
 
 */
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum Direction {
     North,
     West,
@@ -109,7 +110,16 @@ impl Piece {
         }
     }
 
-    fn to_str(&self) -> &str {
+    /// The pipe joining exactly these two directions, in either order.
+    fn from_connections(dirs: &[Direction]) -> Option<Piece> {
+        let pipes = [Piece::Vertical, Piece::Horizontal, Piece::BendNE, Piece::BendNW, Piece::BendSW, Piece::BendSE];
+        pipes.into_iter().find(|p| {
+            let connections = p.connections().unwrap();
+            dirs.len() == 2 && dirs.iter().all(|d| connections.contains(d))
+        })
+    }
+
+    fn to_str(self) -> &'static str {
         match self {
             Piece::Vertical => "┃",
             Piece::Horizontal => "━",
@@ -123,10 +133,7 @@ impl Piece {
     }
 
     fn is_start(&self) -> bool {
-        match self {
-            Piece::Start => true,
-            _ => false,
-        }
+        matches!(self, Piece::Start)
     }
 }
 
@@ -141,24 +148,14 @@ struct Location {
 }
 
 impl Location {
-    fn connects(&self, dir:&Direction, board: &HashMap<(i32, i32), Location>) -> bool {
+    fn connects<B: Tiles>(&self, dir:&Direction, board: &B) -> bool {
         // a none-piece can't connect to anything
         if self.piece == Piece::None {
             return false;
         }
 
-        let mut my_match = false;
-        // a start can connect to anything
-        if self.piece.is_start() {
-            my_match = true;
-        } else {
-            // otherwise our outgoing directions have to match the desired
-            // path
-            if self.piece.connections().unwrap().contains(&dir) {
-                // println!("{:?} contains {:?}", self.piece.connections(), dir);
-                my_match = true;
-            }
-        }
+        // our outgoing directions have to match the desired path
+        let my_match = self.piece.connections().is_some_and(|c| c.contains(dir));
 
         // if we can't match then we can't
         if !my_match {
@@ -166,9 +163,7 @@ impl Location {
         }
 
         // get target location
-        let xy_delta = dir.xy_delta();
-        let tgt_xy = (self.x + xy_delta.0, self.y + xy_delta.1);
-        let tgt_loc_opt = board.get(&tgt_xy);
+        let tgt_loc_opt = board.neighbour((self.x, self.y), dir);
 
         if tgt_loc_opt.is_none() {
             return false;
@@ -180,7 +175,7 @@ impl Location {
         if let Some(connections) = tgt_loc.piece.connections() {
             tgt_match = connections.contains(&dir.inverted());
         }
-        return tgt_match || tgt_loc.piece.is_start();
+        tgt_match
     }
 
     fn travel(&self, dir: &Direction) -> (i32, i32) {
@@ -188,130 +183,154 @@ impl Location {
         (self.x + xy_delta.0, self.y + xy_delta.1)
     }
 
-    fn make_path(&mut self) {
-        self.on_path = true;
-    }
-
     fn make_exit(&mut self) {
         self.is_exit = true;
     }
 }
 
-fn parse_board(input: &String) -> ((i32, i32), HashMap<(i32, i32), Location>) {
-    let mut board: HashMap<(i32, i32), Location> = HashMap::new();
-    let mut y = 0;
+/// Why the sketch can't be walked.
+#[derive(Debug)]
+enum ParseError {
+    NoStart,
+    /// S needs exactly two neighbours whose pipes point back at it to have
+    /// a shape, these are the ones that do
+    StartShape { x: i32, y: i32, connections: Vec<Direction> },
+    /// the pipe the loop reached here doesn't lead on to anything
+    DeadEnd { x: i32, y: i32 },
+}
 
-    let mut start_loc = None;
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::NoStart => write!(f, "no S in the sketch"),
+            ParseError::StartShape { x, y, connections } =>
+                write!(f, "S at ({x}, {y}) has {} connecting neighbours {connections:?}, \
+                    needs exactly 2 to tell its shape", connections.len()),
+            ParseError::DeadEnd { x, y } =>
+                write!(f, "the loop from S dead-ends at ({x}, {y})"),
+        }
+    }
+}
 
-    for l in input.lines() {
-        let mut x = 0;
-        for c in l.chars() {
-            let loc = Location {
-                piece: Piece::from_char(&c),
-                x,
-                y,
-                is_exit: false,
-                synthetic: false,
-                on_path: false,
-            };
-            // print!("{:?}, ", loc);
+/// The pipe hidden under S: whichever one joins the neighbours that point
+/// back at it.
+fn infer_start<B: Tiles>(start_loc: (i32, i32), board: &B) -> Result<Piece, ParseError> {
+    let connections: Vec<Direction> = [Direction::North, Direction::South, Direction::East, Direction::West]
+        .into_iter()
+        .filter(|d| {
+            board.neighbour(start_loc, d)
+                .and_then(|n| n.piece.connections())
+                .is_some_and(|c| c.contains(&d.inverted()))
+        })
+        .collect();
+    Piece::from_connections(&connections).ok_or(ParseError::StartShape {
+        x: start_loc.0,
+        y: start_loc.1,
+        connections,
+    })
+}
 
-            board.insert((x, y), loc);
-            if board.get(&(x,y)).unwrap().piece.is_start() {
-                start_loc = Some((x, y));
-            }
+/// The board with S replaced by the pipe it must be, and where S was.
+fn parse_board<B: Tiles>(input: &str) -> Result<((i32, i32), B), ParseError> {
+    let lines: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0) as i32;
+    let height = lines.len() as i32;
 
-            x = x + 1;
+    let mut start_loc = None;
+
+    // short lines are padded out with ground
+    let mut board = B::filled((0, 0), (width - 1, height - 1), |x, y| {
+        let c = lines[y as usize].get(x as usize).unwrap_or(&'.');
+        let loc = Location {
+            piece: Piece::from_char(c),
+            x,
+            y,
+            is_exit: false,
+            synthetic: false,
+            on_path: false,
+        };
+        if loc.piece.is_start() {
+            start_loc = Some((x, y));
         }
-        // println!();
-        y = y + 1;
-    }
-    return (start_loc.unwrap(), board);
+        loc
+    });
+    let start_loc = start_loc.ok_or(ParseError::NoStart)?;
+    let piece = infer_start(start_loc, &board)?;
+    board.get_mut(&start_loc).unwrap().piece = piece;
+    Ok((start_loc, board))
 }
 
-fn part1(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>) -> Vec<(i32, i32)> {
+/// Every tile of the loop in order, starting from S.
+fn loop_path<B: Tiles>(start_loc: (i32, i32), board: &B) -> Result<Vec<(i32, i32)>, ParseError> {
     let mut path = vec![];
     let mut stop = false;
 
     let mut cur = board.get(&start_loc).unwrap();
     path.push((cur.x, cur.y));
-    let mut moved = &Direction::North;
-
-    let mut distance = 0;
-
     // find first step
-    let dir_options = &vec![Direction::North, Direction::South,Direction::East, Direction::West];
+    let dir_options = &[Direction::North, Direction::South,Direction::East, Direction::West];
     
     // println!("start: {:?}", last);
-    for dir in dir_options {
-        // println!("Checking {:?}", dir);
-        if cur.connects(&dir, &board) {
-            // println!("Match {:?}, travel to {:?}", dir, cur.travel(&dir));
-            cur = board.get(&cur.travel(&dir)).unwrap();
-            moved = &dir;
-            break;
-        }
-    }
+    let first = dir_options.iter().find(|dir| cur.connects(dir, board))
+        .ok_or(ParseError::DeadEnd { x: cur.x, y: cur.y })?;
+    cur = board.get(&cur.travel(first)).unwrap();
+    let mut moved = first;
 
     while !stop {
         path.push((cur.x, cur.y));
         // println!("moved: {:?}", moved);
         // println!("cur: {:?}", cur);
-        for dir in dir_options {
-            if *dir == moved.inverted() {
-                // println!("not checking {:?}", dir);
-                // don't backtrack
-                continue;
-            }
-            if cur.connects(&dir, &board) {
-                cur = board.get(&cur.travel(&dir)).unwrap();
-                moved = dir;
-                break;
-            }
-        }
-        distance = distance+1;
-        stop = cur.piece.is_start();
+        // don't backtrack
+        let next = dir_options.iter()
+            .find(|dir| **dir != moved.inverted() && cur.connects(dir, board))
+            .ok_or(ParseError::DeadEnd { x: cur.x, y: cur.y })?;
+        cur = board.get(&cur.travel(next)).unwrap();
+        moved = next;
+        stop = (cur.x, cur.y) == start_loc;
     }
 
-    println!("distance: {:?}", distance);
-
-    return path;
+    Ok(path)
+}
 
+fn part1(start_loc: (i32, i32), board: &Board) -> Vec<(i32, i32)> {
+    let path = loop_path(start_loc, board).unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    });
+    println!("distance: {:?}", path.len() - 1);
+    let (farthest, tiles) = distance::farthest(&distance::distance_map(board, &path));
+    println!("farthest: {farthest} at {tiles:?}");
+    path
 }
 
-fn reform_board(board: &HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>) -> (HashMap<(i32, i32), Location>, Vec<(i32, i32)>) {
-    let (max_x, max_y) = max_xy(board);
+fn reform_board<B: Tiles>(board: &B, path: &[(i32, i32)]) -> (B, Vec<(i32, i32)>) {
+    let (max_x, max_y) = board.max_xy();
 
     // construct a totally synthetic board
-    let mut new_board = HashMap::new();
-    for x in -1..((2*max_x)+2) {
-        for y in -1..((2 * max_y) + 2) {
-            new_board.insert((x, y), Location {
-                piece: Piece::None,
-                x,
-                y,
-                is_exit: (x == -1) || (y == -1) || (x == 2*max_x+1) || (y == 2*max_y+1),
-                synthetic: true,
-                on_path: false,
-            });
-        }
-    }
+    let mut new_board = B::filled((-1, -1), (2 * max_x + 1, 2 * max_y + 1), |x, y| Location {
+        piece: Piece::None,
+        x,
+        y,
+        is_exit: (x == -1) || (y == -1) || (x == 2*max_x+1) || (y == 2*max_y+1),
+        synthetic: true,
+        on_path: false,
+    });
 
     for x in 0..max_x+1 {
         for y in 0..max_y+1 {
             let original = board.get(&(x, y)).unwrap();
-            new_board.insert((2*x, 2*y), Location {
+            *new_board.get_mut(&(2*x, 2*y)).unwrap() = Location {
                 piece: original.piece,
                 x: 2 * x,
                 y: 2 * y,
                 is_exit: false,
                 synthetic: false,
                 on_path: original.on_path,
-            });
+            };
         }
     }
 
-    let (new_max_x, new_max_y) = max_xy(&new_board);
+    let (new_max_x, new_max_y) = new_board.max_xy();
     for x in -1..new_max_x + 1 {
         for y in -1..new_max_y + 1 {
             let loc = new_board.get(&(x, y)).unwrap();
@@ -329,32 +348,12 @@ fn reform_board(board: &HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>) -
         new_path.push((e.0 * 2, e.1 * 2));
     }
     
-    return (new_board, new_path);
-}
-
-fn min_xy(board: &HashMap<(i32, i32), Location>) -> (i32, i32) {
-    let mut max_x = 0;
-    let mut max_y = 0;
-    for (kx, ky) in board.keys() {
-        max_x = i32::min(max_x, *kx);
-        max_y = i32::min(max_y, *ky);
-    }
-    return (max_x, max_y);
-}
-
-fn max_xy(board: &HashMap<(i32, i32), Location>) -> (i32, i32) {
-    let mut max_x = 0;
-    let mut max_y = 0;
-    for (kx, ky) in board.keys() {
-        max_x = i32::max(max_x, *kx);
-        max_y = i32::max(max_y, *ky);
-    }
-    return (max_x, max_y);
+    (new_board, new_path)
 }
 
-fn print_board(board: &HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>) {
-    let (min_x, min_y) = min_xy(board);
-    let (max_x, max_y) = max_xy(board);
+fn print_board<B: Tiles>(board: &B, path: &[(i32, i32)]) {
+    let (min_x, min_y) = board.min_xy();
+    let (max_x, max_y) = board.max_xy();
 
     for y in min_y..max_y + 1 {
         for x in min_x..max_x + 1 {
@@ -362,25 +361,21 @@ fn print_board(board: &HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>) {
  
             if path.contains(&(x,y)) {
                 print!("{}", loc.piece.to_str().green());
+            } else if loc.is_exit {
+                print!("{}", "x".cyan());
+            } else if loc.synthetic {
+                print!("{}", loc.piece.to_str().blue());
             } else {
-                if loc.is_exit {
-                    print!("{}", "x".cyan());
-                } else {
-                    if loc.synthetic {
-                        print!("{}", loc.piece.to_str().blue());
-                    } else {
-                        print!("{}", loc.piece.to_str());
-                    }
-                }
+                print!("{}", loc.piece.to_str());
             }
         }
         println!();
     }
 }
 
-fn part2(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>) {
-    print_board(board, path);
-
+/// Enclosed tiles found by doubling the board, so gaps between adjacent
+/// pipes become real tiles, and flood filling from the outside.
+fn flood_interior<B: Tiles>(start_loc: (i32, i32), board: &B, path: &[(i32, i32)]) -> usize {
     // println!("{:?}", path);
 
     let (mut reformed, reformed_path) = reform_board(board, path);
@@ -389,7 +384,7 @@ fn part2(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>, path: &Ve
 
     let mut path_index = 0;
     let original = path.index(path_index);
-    path_index = path_index + 1;
+    path_index += 1;
     let next = path.index(path_index);
 
     
@@ -401,12 +396,12 @@ fn part2(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>, path: &Ve
     // println!("delta:    {:?}", next_dir.xy_delta());
     // println!("next_dir: {:?}", next_dir);
 
-    let mut cur = &reformed.get_mut(&new_start_loc).unwrap().clone();
+    let mut cur = reformed.get(&new_start_loc).unwrap().clone();
 
-    while true {
+    loop {
         let synthetic_xy = cur.travel(&next_dir);
 
-        let snapshot_loc = reformed.get_mut(&synthetic_xy).unwrap().clone();;
+        let snapshot_loc = reformed.get(&synthetic_xy).unwrap().clone();
         let updated_loc = Location {
             piece: match next_dir {
                 Direction::North => Piece::Vertical,
@@ -420,16 +415,16 @@ fn part2(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>, path: &Ve
             synthetic: true,
             on_path: true,
         };
-        reformed.insert(synthetic_xy, updated_loc.clone());
-        
-        cur = reformed.get_mut(&updated_loc.travel(&next_dir)).unwrap();
-        if cur.piece.is_start() {
+        *reformed.get_mut(&synthetic_xy).unwrap() = updated_loc.clone();
+
+        cur = reformed.get(&updated_loc.travel(&next_dir)).unwrap().clone();
+        if (cur.x, cur.y) == new_start_loc {
             break;
         }
 
         // println!("{} -> {}", path_index, (path_index + 1) % path.len());
         next_dir = Direction::heading(*path.index(path_index), *path.index((path_index + 1) % path.len()));
-        path_index = path_index + 1;
+        path_index += 1;
     }
     // print_board(&reformed, &reformed_path);
 
@@ -438,7 +433,7 @@ fn part2(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>, path: &Ve
     // print_board(&reformed, &reformed_path);
 
     let mut acc = 0;
-    for v in reformed.values() {
+    for v in reformed.locations() {
         let v_xy = (v.x, v.y);
         if v.synthetic || v.is_exit {
             continue;
@@ -446,24 +441,28 @@ fn part2(start_loc: (i32, i32), board: &HashMap<(i32, i32), Location>, path: &Ve
         if reformed_path.contains(&v_xy) || v.on_path {
             continue;
         }
-        acc = acc + 1;
+        acc += 1;
         // println!("{:?}", v_xy);
     }
-    println!("{:?}", acc);
+    acc
 }
 
-fn flood_board(board: &mut HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>) {
+fn flood_board<B: Tiles>(board: &mut B, path: &[(i32, i32)]) {
     let mut eval = vec![(-1, -1)];
-    let mut seen = HashSet::new();
+    let (min_x, min_y) = board.min_xy();
+    let (max_x, max_y) = board.max_xy();
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+    let mut seen = Grid::new((min_x, min_y), width, height, |_, _| false);
 
-    let dirs = vec![Direction::North, Direction::South, Direction::East, Direction::West];
+    let dirs = [Direction::North, Direction::South, Direction::East, Direction::West];
 
-    while eval.len() > 0 {
-        let e = eval.pop().unwrap();
-        if seen.contains(&e) {
+    while let Some(e) = eval.pop() {
+        let seen_e = seen.cell_mut(e).unwrap();
+        if *seen_e {
             continue;
         }
-        seen.insert(e);
+        *seen_e = true;
         {
             board.get_mut(&e).unwrap().make_exit();
         }
@@ -473,7 +472,7 @@ fn flood_board(board: &mut HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>
             let new_xy = cur_loc.travel(d);
             if let Some(new_loc) = board.get(&new_xy) {
                 let new_xy = (new_loc.x, new_loc.y);
-                if !path.contains(&new_xy) && !(new_loc.synthetic && new_loc.on_path) {
+                if !(path.contains(&new_xy) || new_loc.synthetic && new_loc.on_path) {
                     eval.push(new_xy);
                 }
             }
@@ -481,13 +480,181 @@ fn flood_board(board: &mut HashMap<(i32, i32), Location>, path: &Vec<(i32, i32)>
     }
 }
 
+/// Parse, walk and flood fill `input` `rounds` times on the given storage,
+/// returning the enclosed count and how long it all took.
+fn time_pipeline<B: Tiles>(input: &str, rounds: usize) -> (usize, std::time::Duration) {
+    let began = Instant::now();
+    let mut enclosed = 0;
+    for _ in 0..rounds {
+        let (start_loc, board) = parse_board::<B>(input).expect("a walkable sketch");
+        let path = loop_path(start_loc, &board).expect("a loop");
+        enclosed = flood_interior(start_loc, &board, &path);
+    }
+    (enclosed, began.elapsed())
+}
+
+/// Times the grid against the original HashMap board.
+fn bench(input: &str, rounds: usize) {
+    let (_, board) = parse_board::<Board>(input).expect("a walkable sketch");
+    println!("{}x{} board", board.width(), board.height());
+    let (grid_enclosed, grid_time) = time_pipeline::<Grid<Location>>(input, rounds);
+    let (map_enclosed, map_time) = time_pipeline::<HashMap<(i32, i32), Location>>(input, rounds);
+    assert_eq!(grid_enclosed, map_enclosed, "grid and HashMap boards disagree");
+
+    println!("{rounds} rounds, {grid_enclosed} enclosed");
+    println!("grid:    {:?} ({:?} per round)", grid_time, grid_time / rounds as u32);
+    println!("hashmap: {:?} ({:?} per round)", map_time, map_time / rounds as u32);
+    println!("speedup: {:.1}x", map_time.as_secs_f64() / grid_time.as_secs_f64());
+}
+
+/// Writes the board to `out` as SVG or an animated GIF, going by its
+/// extension.
+fn render(board: &Board, start_loc: (i32, i32), path: &[(i32, i32)], out: &str, scale: usize) {
+    let interior = scanline::interior(board, path);
+    let written = if scale == 0 {
+        Err(String::from("--scale must be at least 1"))
+    } else if out.ends_with(".svg") {
+        std::fs::write(out, render::svg(board, start_loc, path, &interior, scale)).map_err(|e| e.to_string())
+    } else if out.ends_with(".gif") {
+        std::fs::File::create(out)
+            .map_err(|e| e.to_string())
+            .and_then(|f| render::gif(board, start_loc, path, &interior, scale, f).map_err(|e| e.to_string()))
+    } else {
+        Err(String::from("can only render .svg or .gif"))
+    };
+    if let Err(e) = written {
+        eprintln!("error: {out}: {e}");
+        std::process::exit(1);
+    }
+    println!("wrote {out}");
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // --method flood|pick|scan|check, check runs all three and insists they
+    // agree
+    let mut method = String::from("flood");
+    if let Some(idx) = args.iter().position(|a| a == "--method") {
+        args.remove(idx);
+        if idx >= args.len() {
+            panic!("--method needs flood, pick, scan or check");
+        }
+        method = args.remove(idx);
+    }
+
+    // day10 bench [rounds] [input]
+    let mut bench_rounds = None;
+    if args.first().is_some_and(|a| a == "bench") {
+        args.remove(0);
+        let rounds = match args.first().and_then(|a| a.parse().ok()) {
+            Some(rounds) => {
+                args.remove(0);
+                rounds
+            },
+            None => 10,
+        };
+        bench_rounds = Some(rounds);
+    }
+
+    // --scale <pixels per tile> for render
+    let mut scale = 8;
+    if let Some(idx) = args.iter().position(|a| a == "--scale") {
+        args.remove(idx);
+        if idx >= args.len() {
+            panic!("--scale needs a number of pixels");
+        }
+        scale = args.remove(idx).parse().expect("--scale pixels");
+    }
+
+    // day10 render <out.svg|out.gif> [input] [--scale <pixels>]
+    let mut render_to = None;
+    if args.first().is_some_and(|a| a == "render") {
+        args.remove(0);
+        if args.is_empty() {
+            panic!("usage: day10 render <out.svg|out.gif> [input] [--scale <pixels>]");
+        }
+        render_to = Some(args.remove(0));
+    }
+
+    // day10 distances [input]
+    let distances = args.first().is_some_and(|a| a == "distances");
+    if distances {
+        args.remove(0);
+    }
+
+    // day10 [input] [--method <method>]
     let default_path = String::from("test.txt");
-    let fp = args.get(1).unwrap_or(&default_path);
+    let fp = args.first().unwrap_or(&default_path);
 
     let input = std::fs::read_to_string(fp).expect("input");
-    let (start_loc, mut board) = parse_board(&input);
-    let path = part1(start_loc, &mut board);
-    part2(start_loc, &mut board, &path);
-}
\ No newline at end of file
+    if let Some(rounds) = bench_rounds {
+        bench(&input, rounds);
+        return;
+    }
+    let (start_loc, board) = match parse_board(&input) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        },
+    };
+    let path = part1(start_loc, &board);
+    if distances {
+        print!("{}", distance::render(&board, &distance::distance_map(&board, &path)));
+        return;
+    }
+    if let Some(out) = render_to {
+        render(&board, start_loc, &path, &out, scale);
+        return;
+    }
+    print_board(&board, &path);
+    let enclosed = match method.as_str() {
+        "flood" => flood_interior(start_loc, &board, &path),
+        "pick" => area::pick_interior(&path),
+        "scan" => scanline::interior(&board, &path).len(),
+        "check" => {
+            let flood = flood_interior(start_loc, &board, &path);
+            let pick = area::pick_interior(&path);
+            let scan = scanline::interior(&board, &path).len();
+            assert_eq!(flood, pick, "flood fill and Pick's theorem disagree");
+            assert_eq!(flood, scan, "flood fill and the scanline disagree");
+            flood
+        },
+        _ => panic!("--method is flood, pick, scan or check, not '{method}'"),
+    };
+    println!("{:?}", enclosed);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A fixture's S, board and loop.
+    pub(crate) fn solved(input: &str) -> ((i32, i32), Board, Vec<(i32, i32)>) {
+        let (start_loc, board) = parse_board::<Board>(input).unwrap();
+        let path = loop_path(start_loc, &board).unwrap();
+        (start_loc, board, path)
+    }
+
+    fn start_connections(input: &str) -> Vec<Direction> {
+        match parse_board::<Board>(input) {
+            Err(ParseError::StartShape { connections, .. }) => connections,
+            other => panic!("expected a bad S, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn start_needs_two_neighbours() {
+        assert_eq!(start_connections("S"), vec![]);
+        assert_eq!(start_connections("S-\n.."), vec![Direction::East]);
+        assert_eq!(start_connections(".|.\n-S-\n..."), vec![Direction::North, Direction::East, Direction::West]);
+        assert!(matches!(parse_board::<Board>("..\n.."), Err(ParseError::NoStart)));
+    }
+
+    #[test]
+    fn dead_end() {
+        let (start_loc, board) = parse_board::<Board>("S-.\n|..").unwrap();
+        assert!(matches!(loop_path(start_loc, &board), Err(ParseError::DeadEnd { x: 0, y: 1 })));
+    }
+}
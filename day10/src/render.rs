@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs::File;
+
+use crate::grid::{Grid, Tiles};
+use crate::{Board, Direction};
+
+// tiles per frame are chosen so each phase of the animation runs to about
+// this many frames
+const TRACE_FRAMES: usize = 120;
+const FILL_FRAMES: usize = 60;
+// hundredths of a second
+const FRAME_DELAY: u16 = 4;
+const FINAL_DELAY: u16 = 300;
+
+/// What a tile is once the puzzle's solved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Exterior,
+    Interior,
+    Loop,
+    Start,
+}
+
+fn kinds(board: &Board, start_loc: (i32, i32), path: &[(i32, i32)], interior: &[(i32, i32)]) -> Grid<Kind> {
+    let on_loop: HashSet<&(i32, i32)> = path.iter().collect();
+    let inside: HashSet<&(i32, i32)> = interior.iter().collect();
+    Grid::new(board.min_xy(), board.width(), board.height(), |x, y| {
+        if (x, y) == start_loc {
+            Kind::Start
+        } else if on_loop.contains(&(x, y)) {
+            Kind::Loop
+        } else if inside.contains(&(x, y)) {
+            Kind::Interior
+        } else {
+            Kind::Exterior
+        }
+    })
+}
+
+/// The board as SVG, one `scale`-pixel square per tile: exterior and
+/// interior tiles shaded, pipes off the loop drawn faintly and the loop
+/// itself as a single closed path, with S marked.
+pub fn svg(board: &Board, start_loc: (i32, i32), path: &[(i32, i32)], interior: &[(i32, i32)], scale: usize) -> String {
+    let kinds = kinds(board, start_loc, path, interior);
+    let s = scale as f64;
+    let centre = |(x, y): (i32, i32)| (x as f64 * s + s / 2.0, y as f64 * s + s / 2.0);
+
+    let mut out = String::new();
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        board.width() * scale, board.height() * scale).unwrap();
+    writeln!(out, "<style>").unwrap();
+    writeln!(out, "  .exterior {{ fill: #eeeeee; }}").unwrap();
+    writeln!(out, "  .interior {{ fill: #8fd18f; }}").unwrap();
+    writeln!(out, "  .start {{ fill: #e04040; }}").unwrap();
+    writeln!(out, "  .pipe {{ stroke: #bbbbbb; stroke-width: {:.1}; fill: none; }}", s / 8.0).unwrap();
+    writeln!(out, "  .loop {{ stroke: #2060d0; stroke-width: {:.1}; fill: none; stroke-linejoin: round; }}", s / 4.0).unwrap();
+    writeln!(out, "</style>").unwrap();
+
+    for (loc, kind) in board.locations().zip(kinds.iter().map(|(_, k)| k)) {
+        let class = match kind {
+            Kind::Exterior => "exterior",
+            Kind::Interior => "interior",
+            Kind::Start => "start",
+            Kind::Loop => continue,
+        };
+        writeln!(out, "<rect class=\"{class}\" x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\"/>",
+            loc.x as f64 * s, loc.y as f64 * s).unwrap();
+    }
+
+    // junk pipes, each arm from the tile's centre to its edge
+    for (loc, kind) in board.locations().zip(kinds.iter().map(|(_, k)| k)) {
+        let Some(connections) = loc.piece.connections().filter(|_| *kind != Kind::Loop && *kind != Kind::Start) else {
+            continue;
+        };
+        let (cx, cy) = centre((loc.x, loc.y));
+        let mut d = String::new();
+        for dir in connections {
+            let (dx, dy) = dir.xy_delta();
+            write!(d, "M{cx} {cy}l{} {}", dx as f64 * s / 2.0, dy as f64 * s / 2.0).unwrap();
+        }
+        writeln!(out, "<path class=\"pipe\" d=\"{d}\"/>").unwrap();
+    }
+
+    let points: Vec<String> = path.iter()
+        .map(|xy| {
+            let (x, y) = centre(*xy);
+            format!("{x},{y}")
+        })
+        .collect();
+    writeln!(out, "<polygon class=\"loop\" points=\"{}\"/>", points.join(" ")).unwrap();
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+// palette entries
+const UNKNOWN: u8 = 0;
+const PIPE: u8 = 1;
+const TRACED: u8 = 2;
+const START: u8 = 3;
+const EXTERIOR: u8 = 4;
+const INTERIOR: u8 = 5;
+const PALETTE: [u8; 18] = [
+    0x30, 0x30, 0x30,
+    0x80, 0x80, 0x80,
+    0x20, 0x60, 0xd0,
+    0xe0, 0x40, 0x40,
+    0xee, 0xee, 0xee,
+    0x8f, 0xd1, 0x8f,
+];
+
+/// Indexed pixels for the whole board, `scale` pixels a tile.
+struct Canvas {
+    width: usize,
+    scale: usize,
+    min: (i32, i32),
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn fill(&mut self, (x, y): (i32, i32), colour: u8) {
+        let (px, py) = ((x - self.min.0) as usize * self.scale, (y - self.min.1) as usize * self.scale);
+        for row in py..py + self.scale {
+            let start = row * self.width + px;
+            self.pixels[start..start + self.scale].fill(colour);
+        }
+    }
+
+    /// The pipe as a plus-shaped stroke with only the arms it connects.
+    fn pipe(&mut self, (x, y): (i32, i32), connections: &[Direction], colour: u8) {
+        let s = self.scale as i32;
+        let (px, py) = ((x - self.min.0) * s, (y - self.min.1) * s);
+        let (cx, cy) = (px + s / 2, py + s / 2);
+        let mut set = |x: i32, y: i32| self.pixels[y as usize * self.width + x as usize] = colour;
+        set(cx, cy);
+        for dir in connections {
+            let (dx, dy) = dir.xy_delta();
+            let (mut x, mut y) = (cx, cy);
+            while (px..px + s).contains(&(x + dx)) && (py..py + s).contains(&(y + dy)) {
+                x += dx;
+                y += dy;
+                set(x, y);
+            }
+        }
+    }
+}
+
+/// Tiles of one kind in the order a breadth-first fill reaches them,
+/// starting from those touching the edge of the board or the loop. Pockets
+/// it can't reach (exterior tiles squeezed between pipes) start a fill of
+/// their own once the rest is done.
+fn fill_order(kinds: &Grid<Kind>, kind: Kind) -> Vec<(i32, i32)> {
+    let dirs = [Direction::North, Direction::South, Direction::East, Direction::West];
+    let borders = |xy: (i32, i32)| dirs.iter().any(|d| {
+        let (dx, dy) = d.xy_delta();
+        kinds.cell((xy.0 + dx, xy.1 + dy)).is_none_or(|k| *k == Kind::Loop || *k == Kind::Start)
+    });
+
+    let mut seen = HashSet::new();
+    let mut order = vec![];
+    let mut pending: Vec<(i32, i32)> = kinds.iter()
+        .filter(|(xy, k)| **k == kind && borders(*xy))
+        .map(|(xy, _)| xy)
+        .collect();
+    let mut leftovers = kinds.iter().filter(|(_, k)| **k == kind).map(|(xy, _)| xy);
+    let mut idx = 0;
+    loop {
+        for xy in pending.drain(..) {
+            if seen.insert(xy) {
+                order.push(xy);
+            }
+        }
+        while idx < order.len() {
+            let (x, y) = order[idx];
+            idx += 1;
+            for d in dirs.iter() {
+                let (dx, dy) = d.xy_delta();
+                let next = (x + dx, y + dy);
+                if kinds.cell(next) == Some(&kind) && seen.insert(next) {
+                    order.push(next);
+                }
+            }
+        }
+        match leftovers.find(|xy| !seen.contains(xy)) {
+            Some(xy) => pending.push(xy),
+            None => return order,
+        }
+    }
+}
+
+/// An animated GIF of the loop being traced from S, then the exterior and
+/// interior filling in.
+pub fn gif(
+    board: &Board,
+    start_loc: (i32, i32),
+    path: &[(i32, i32)],
+    interior: &[(i32, i32)],
+    scale: usize,
+    file: File,
+) -> Result<(), gif::EncodingError> {
+    // GIF dimensions are 16 bit
+    let size = |tiles: usize| tiles.checked_mul(scale).filter(|px| (1..=u16::MAX as usize).contains(px));
+    let (Some(width), Some(height)) = (size(board.width()), size(board.height())) else {
+        let msg = format!("{}x{} tiles at scale {scale} doesn't fit in a GIF, which allows 1 to {} pixels a side",
+            board.width(), board.height(), u16::MAX);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into());
+    };
+    let kinds = kinds(board, start_loc, path, interior);
+    let mut canvas = Canvas { width, scale, min: board.min_xy(), pixels: vec![UNKNOWN; width * height] };
+    for loc in board.locations() {
+        if let Some(connections) = loc.piece.connections() {
+            canvas.pipe((loc.x, loc.y), &connections, PIPE);
+        }
+    }
+
+    let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &PALETTE)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    let mut frame = |canvas: &Canvas, delay: u16| {
+        let frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            buffer: std::borrow::Cow::Borrowed(&canvas.pixels),
+            delay,
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame)
+    };
+
+    let per_frame = path.len().div_ceil(TRACE_FRAMES).max(1);
+    for steps in path.chunks(per_frame) {
+        for xy in steps {
+            let colour = if *xy == start_loc { START } else { TRACED };
+            canvas.pipe(*xy, &board.get(xy).unwrap().piece.connections().unwrap(), colour);
+        }
+        frame(&canvas, FRAME_DELAY)?;
+    }
+
+    for (kind, colour) in [(Kind::Exterior, EXTERIOR), (Kind::Interior, INTERIOR)] {
+        let order = fill_order(&kinds, kind);
+        let per_frame = order.len().div_ceil(FILL_FRAMES).max(1);
+        for tiles in order.chunks(per_frame) {
+            for xy in tiles {
+                canvas.fill(*xy, colour);
+                if let Some(connections) = board.get(xy).unwrap().piece.connections() {
+                    canvas.pipe(*xy, &connections, PIPE);
+                }
+            }
+            frame(&canvas, FRAME_DELAY)?;
+        }
+    }
+    frame(&canvas, FINAL_DELAY)
+}
@@ -0,0 +1,47 @@
+use crate::grid::{Grid, Tiles};
+use crate::{Board, Direction, Location};
+
+/// Tiles enclosed by the loop, found by scanning each row left to right and
+/// flipping between outside and inside on every loop tile that connects
+/// north (|, L and J). A run like L-7 crosses the loop once and L-J not at
+/// all, and counting only the north half of each bend gets both right.
+/// S has already been swapped for its real pipe, so it needs no special
+/// case. The tiles come back in row order, each once.
+pub fn interior(board: &Board, path: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut on_loop = Grid::new(board.min_xy(), board.width(), board.height(), |_, _| false);
+    for xy in path {
+        *on_loop.cell_mut(*xy).unwrap() = true;
+    }
+
+    let mut inside_tiles = vec![];
+    for ((y, row), (_, loop_row)) in board.rows().zip(on_loop.rows()) {
+        let mut inside = false;
+        for (loc, looped) in row.iter().zip(loop_row.iter()) {
+            if *looped {
+                inside ^= connects_north(loc);
+            } else if inside {
+                inside_tiles.push((loc.x, y));
+            }
+        }
+    }
+    inside_tiles
+}
+
+fn connects_north(loc: &Location) -> bool {
+    loc.piece.connections().is_some_and(|c| c.contains(&Direction::North))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::solved;
+
+    #[test]
+    fn exact_tiles() {
+        let (_, board, path) = solved(include_str!("../test.txt"));
+        assert_eq!(interior(&board, &path), vec![(2, 2)]);
+
+        let (_, board, path) = solved(include_str!("../test2.txt"));
+        assert_eq!(interior(&board, &path), vec![(14, 3), (7, 4), (8, 4), (9, 4), (7, 5), (8, 5), (6, 6), (14, 6)]);
+    }
+}
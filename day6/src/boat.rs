@@ -0,0 +1,235 @@
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use std::fmt;
+
+use crate::Race;
+
+/// Why `winning_window` couldn't answer.
+#[derive(Debug, PartialEq)]
+pub enum WindowError {
+    /// the model has no closed form and the charge times don't fit in u64
+    TooLongToScan { time: BigUint },
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowError::TooLongToScan { time } =>
+                write!(f, "race of {time} ms is too long to scan and this boat has no closed form"),
+        }
+    }
+}
+
+/// How a boat turns charge time into distance.
+///
+/// The puzzle's boat is `BoatModel::default()`: +1 mm/ms of speed per ms of
+/// charge, no top speed, no drag, and any charge time from 0 to the race
+/// time.
+#[derive(Debug, Clone)]
+pub struct BoatModel {
+    /// speed gained per ms of charge
+    pub rate: u64,
+    /// speed can't exceed this no matter how long we charge
+    pub top_speed: Option<u64>,
+    /// speed lost per ms once the boat is moving, it stops at zero
+    pub decay: Option<u64>,
+    /// shortest allowed charge
+    pub min_charge: Option<u64>,
+    /// longest allowed charge
+    pub max_charge: Option<u64>,
+}
+
+impl Default for BoatModel {
+    fn default() -> Self {
+        BoatModel {
+            rate: 1,
+            top_speed: None,
+            decay: None,
+            min_charge: None,
+            max_charge: None,
+        }
+    }
+}
+
+impl BoatModel {
+    pub fn speed(&self, charge_time: &BigUint) -> BigUint {
+        let speed = charge_time * self.rate;
+        match self.top_speed {
+            Some(top) if speed > BigUint::from(top) => BigUint::from(top),
+            _ => speed,
+        }
+    }
+
+    pub fn travelled(&self, race: &Race, charge_time: &BigUint) -> BigUint {
+        if *charge_time > race.time {
+            return BigUint::zero();
+        }
+        let speed = self.speed(charge_time);
+        let remaining = &race.time - charge_time;
+
+        match self.decay {
+            None | Some(0) => speed * remaining,
+            Some(decay) => {
+                // the boat moves for ceil(speed / decay) ms before stopping,
+                // covering speed, speed - decay, speed - 2 * decay, ...
+                let moving = (&speed + (decay - 1)) / decay;
+                let steps = moving.min(remaining);
+                if steps.is_zero() {
+                    return steps;
+                }
+                &steps * speed - (&steps * (&steps - 1u32) / 2u32) * decay
+            },
+        }
+    }
+
+    /// Shortest and longest charge times allowed in `race`, None if the
+    /// limits leave nothing.
+    pub fn charge_range(&self, race: &Race) -> Option<(BigUint, BigUint)> {
+        let lo = BigUint::from(self.min_charge.unwrap_or(0));
+        let hi = match self.max_charge {
+            Some(max) => race.time.clone().min(BigUint::from(max)),
+            None => race.time.clone(),
+        };
+        if lo > hi {
+            None
+        } else {
+            Some((lo, hi))
+        }
+    }
+
+    /// Whether `winning_window` can be solved directly rather than by trying
+    /// every charge time.
+    pub fn has_closed_form(&self) -> bool {
+        self.top_speed.is_none() && self.decay.unwrap_or(0) == 0
+    }
+
+    /// First and last winning charge times, inclusive, along with how many
+    /// charge times in between win. None if nothing wins.
+    pub fn winning_window(&self, race: &Race) -> Result<Option<(BigUint, BigUint, BigUint)>, WindowError> {
+        let Some((min_charge, max_charge)) = self.charge_range(race) else {
+            return Ok(None);
+        };
+        if !self.has_closed_form() {
+            return self.scan(race, min_charge, max_charge);
+        }
+
+        let Some((lo, hi)) = self.solve_linear(race) else {
+            return Ok(None);
+        };
+        let (lo, hi) = (lo.max(min_charge), hi.min(max_charge));
+        if lo > hi {
+            return Ok(None);
+        }
+        let count = &hi - &lo + BigUint::one();
+        Ok(Some((lo, hi, count)))
+    }
+
+    fn solve_linear(&self, race: &Race) -> Option<(BigUint, BigUint)> {
+        // rate * t * (time - t) > distance is a downward parabola symmetric
+        // around time / 2, so solve
+        // rate * t^2 - rate * time * t + distance = 0 and walk the (floored)
+        // root up until it actually wins.
+        if self.rate == 0 {
+            return None;
+        }
+        let a_time = &race.time * self.rate;
+        let four_ad = &race.distance * (4 * self.rate);
+        let square = &a_time * &a_time;
+        if square < four_ad {
+            return None;
+        }
+        let root = (square - four_ad).sqrt();
+
+        let mid = &race.time / 2u32;
+        let mut lo = (a_time - root) / (2 * self.rate);
+        while self.travelled(race, &lo) <= race.distance {
+            if lo >= mid {
+                return None;
+            }
+            lo += 1u32;
+        }
+
+        let hi = &race.time - &lo;
+        Some((lo, hi))
+    }
+
+    fn scan(
+        &self,
+        race: &Race,
+        min_charge: BigUint,
+        max_charge: BigUint,
+    ) -> Result<Option<(BigUint, BigUint, BigUint)>, WindowError> {
+        let (Some(from), Some(to)) = (min_charge.to_u64(), max_charge.to_u64()) else {
+            return Err(WindowError::TooLongToScan { time: race.time.clone() });
+        };
+
+        let mut window: Option<(u64, u64)> = None;
+        let mut count: u64 = 0;
+        for charge_time in from..=to {
+            if self.travelled(race, &BigUint::from(charge_time)) > race.distance {
+                count += 1;
+                window = match window {
+                    Some((lo, _)) => Some((lo, charge_time)),
+                    None => Some((charge_time, charge_time)),
+                };
+            }
+        }
+
+        Ok(window.map(|(lo, hi)| (BigUint::from(lo), BigUint::from(hi), BigUint::from(count))))
+    }
+
+    pub fn from_args(args: &mut Vec<String>) -> BoatModel {
+        let mut model = BoatModel::default();
+        if let Some(rate) = crate::take_flag(args, "--rate") {
+            model.rate = rate.parse().expect("--rate number");
+        }
+        model.top_speed = crate::take_flag(args, "--top-speed").map(|v| v.parse().expect("--top-speed number"));
+        model.decay = crate::take_flag(args, "--decay").map(|v| v.parse().expect("--decay number"));
+        model.min_charge = crate::take_flag(args, "--min-charge").map(|v| v.parse().expect("--min-charge number"));
+        model.max_charge = crate::take_flag(args, "--max-charge").map(|v| v.parse().expect("--max-charge number"));
+        model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Day6, Kerning};
+
+    fn test_races() -> Vec<Race> {
+        Day6::parse_sheets(include_str!("../test.txt"), Kerning::Spaced).unwrap().remove(0)
+    }
+
+    #[test]
+    fn closed_form_matches_scan() {
+        for rate in [1, 2, 3, 7] {
+            for (min_charge, max_charge) in [(None, None), (Some(3), None), (None, Some(4)), (Some(5), Some(11)), (Some(9), Some(2))] {
+                let model = BoatModel { rate, min_charge, max_charge, ..BoatModel::default() };
+                assert!(model.has_closed_form());
+                for race in test_races() {
+                    let scanned = match model.charge_range(&race) {
+                        Some((lo, hi)) => model.scan(&race, lo, hi),
+                        None => Ok(None),
+                    };
+                    assert_eq!(model.winning_window(&race), scanned, "{model:?} {race:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn puzzle_boat() {
+        let counts: Vec<BigUint> = test_races().iter()
+            .map(|race| BoatModel::default().winning_window(race).unwrap().unwrap().2)
+            .collect();
+        assert_eq!(counts, vec![4u32.into(), 8u32.into(), 9u32.into()]);
+    }
+
+    #[test]
+    fn too_long_to_scan() {
+        let race = Race { time: "123456789012345678901234567890".parse().unwrap(), distance: BigUint::from(9u32) };
+        let model = BoatModel { top_speed: Some(3), ..BoatModel::default() };
+        assert!(matches!(model.winning_window(&race), Err(WindowError::TooLongToScan { .. })));
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+use crate::boat::BoatModel;
+use crate::Race;
+
+// past this many charge times the curve is too long to be worth printing or
+// plotting point by point
+const CURVE_LIMIT: u64 = 1000;
+
+const PLOT_WIDTH: f64 = 640.0;
+const PLOT_HEIGHT: f64 = 400.0;
+const PLOT_MARGIN: f64 = 40.0;
+
+impl Race {
+    /// (charge_time, distance) for every charge time the model allows, if
+    /// there are few enough to enumerate.
+    fn curve(&self, model: &BoatModel) -> Option<Vec<(u64, BigUint)>> {
+        let Some((lo, hi)) = model.charge_range(self) else {
+            return Some(vec![]);
+        };
+        let lo = lo.to_u64()?;
+        let hi = hi.to_u64().filter(|hi| hi - lo <= CURVE_LIMIT)?;
+        let mut v = vec![];
+        for charge_time in lo..=hi {
+            v.push((charge_time, model.travelled(self, &BigUint::from(charge_time))));
+        }
+        Some(v)
+    }
+
+    /// Allowed charge times that travel furthest; two of them when the peak
+    /// is shared. None when the race is too long to scan and the model has
+    /// no closed form to find the peak with.
+    fn optimal_charge_times(&self, model: &BoatModel) -> Option<Vec<BigUint>> {
+        let candidates: Vec<BigUint> = match self.curve(model) {
+            Some(curve) => curve.into_iter().map(|(t, _)| BigUint::from(t)).collect(),
+            None if model.has_closed_form() => {
+                // rate * t * (time - t) peaks either side of time / 2, or at
+                // whichever end of the allowed range is nearest
+                let (min_charge, max_charge) = model.charge_range(self)?;
+                let lo = &self.time / 2u32;
+                let hi = &self.time - &lo;
+                let mut peak: Vec<BigUint> = [lo, hi].into_iter()
+                    .map(|t| t.max(min_charge.clone()).min(max_charge.clone()))
+                    .collect();
+                peak.dedup();
+                peak
+            },
+            None => return None,
+        };
+
+        let best = candidates.iter().map(|t| model.travelled(self, t)).max().unwrap_or_default();
+        Some(candidates.into_iter()
+            .filter(|t| model.travelled(self, t) == best)
+            .collect())
+    }
+}
+
+pub fn explore(sheet: usize, races: &[Race], model: &BoatModel, plot_dir: Option<&Path>) {
+    for (idx, race) in races.iter().enumerate() {
+        println!("sheet {sheet} race {idx}: time {} record {}", race.time, race.distance);
+
+        match race.curve(model) {
+            Some(curve) => {
+                for (charge_time, distance) in curve.iter() {
+                    let marker = if *distance > race.distance { "*" } else { " " };
+                    println!("  {marker} {charge_time:>4} -> {distance}");
+                }
+            },
+            None => {
+                println!("  (curve omitted, more than {CURVE_LIMIT} charge times)");
+            },
+        }
+
+        match race.optimal_charge_times(model) {
+            Some(optimal) if !optimal.is_empty() => {
+                let best = model.travelled(race, &optimal[0]);
+                let optimal_str: Vec<String> = optimal.iter().map(|t| t.to_string()).collect();
+                println!("  optimal charge: {} -> {}", optimal_str.join(" / "), best);
+
+                if best > race.distance {
+                    println!("  margin at optimum: {}", &best - &race.distance);
+                } else {
+                    println!("  margin at optimum: -{}", &race.distance - &best);
+                }
+            },
+            Some(_) => println!("  optimal charge: none, no charge time is allowed"),
+            None => println!("  optimal charge: unknown, race too long to scan and no closed form"),
+        }
+
+        match model.winning_window(race) {
+            Ok(Some((lo, hi, count))) => println!("  winning window: {lo}..={hi} ({count} ways)"),
+            Ok(None) => println!("  winning window: none"),
+            Err(e) => println!("  winning window: unknown, {e}"),
+        }
+
+        if let Some(dir) = plot_dir {
+            let fp = dir.join(format!("sheet{sheet}-race{idx}.svg"));
+            match plot_svg(race, model) {
+                Some(svg) => {
+                    std::fs::write(&fp, svg).expect("write plot");
+                    println!("  plot: {}", fp.display());
+                },
+                None => println!("  plot: skipped, race too long"),
+            }
+        }
+        println!();
+    }
+}
+
+fn plot_svg(race: &Race, model: &BoatModel) -> Option<String> {
+    let curve: Vec<(u64, u64)> = race.curve(model)?
+        .into_iter()
+        .map(|(t, d)| d.to_u64().map(|d| (t, d)))
+        .collect::<Option<_>>()?;
+    let record = race.distance.to_u64()?;
+
+    let max_x = curve.last().map(|(t, _)| *t).unwrap_or(0).max(1) as f64;
+    let max_y = curve.iter().map(|(_, d)| *d).max().unwrap_or(0).max(record).max(1) as f64;
+    let inner_w = PLOT_WIDTH - 2.0 * PLOT_MARGIN;
+    let inner_h = PLOT_HEIGHT - 2.0 * PLOT_MARGIN;
+    let px = |x: f64| PLOT_MARGIN + x / max_x * inner_w;
+    let py = |y: f64| PLOT_HEIGHT - PLOT_MARGIN - y / max_y * inner_h;
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{PLOT_WIDTH}" height="{PLOT_HEIGHT}">"#).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    if let Ok(Some((lo, hi, _))) = model.winning_window(race) {
+        let lo = lo.to_f64().unwrap_or(0.0);
+        let hi = hi.to_f64().unwrap_or(0.0);
+        writeln!(svg, r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#cfc"/>"##,
+            px(lo), PLOT_MARGIN, px(hi) - px(lo), inner_h).unwrap();
+    }
+
+    // axes
+    writeln!(svg, r#"<line x1="{0}" y1="{1}" x2="{2}" y2="{1}" stroke="black"/>"#,
+        PLOT_MARGIN, PLOT_HEIGHT - PLOT_MARGIN, PLOT_WIDTH - PLOT_MARGIN).unwrap();
+    writeln!(svg, r#"<line x1="{0}" y1="{1}" x2="{0}" y2="{2}" stroke="black"/>"#,
+        PLOT_MARGIN, PLOT_MARGIN, PLOT_HEIGHT - PLOT_MARGIN).unwrap();
+
+    // record to beat
+    writeln!(svg, r#"<line x1="{0}" y1="{1}" x2="{2}" y2="{1}" stroke="red" stroke-dasharray="4"/>"#,
+        PLOT_MARGIN, py(record as f64), PLOT_WIDTH - PLOT_MARGIN).unwrap();
+
+    let points: Vec<String> = curve.iter()
+        .map(|(t, d)| format!("{:.1},{:.1}", px(*t as f64), py(*d as f64)))
+        .collect();
+    writeln!(svg, r#"<polyline points="{}" fill="none" stroke="blue"/>"#, points.join(" ")).unwrap();
+
+    writeln!(svg, r#"<text x="{}" y="{}" font-size="12">charge time (ms)</text>"#,
+        PLOT_WIDTH / 2.0, PLOT_HEIGHT - 10.0).unwrap();
+    writeln!(svg, r#"<text x="5" y="{}" font-size="12">distance (mm), record {}</text>"#,
+        PLOT_MARGIN - 10.0, record).unwrap();
+    writeln!(svg, "</svg>").unwrap();
+
+    Some(svg)
+}
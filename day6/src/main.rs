@@ -1,106 +1,306 @@
 use std::env;
+use std::fmt;
+use std::path::Path;
 use regex::Regex;
 use once_cell::sync::Lazy;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use boat::{BoatModel, WindowError};
 // use std::collections::HashMap;
 
-const NUMBERS_RE: Lazy<Regex> = Lazy::new(||
-    Regex::new(r".*:(.*)").expect("regex")
+mod boat;
+mod explore;
+
+static LINE_RE: Lazy<Regex> = Lazy::new(||
+    Regex::new(r"^\s*(\w+):(.*)$").expect("regex")
 );
 
 #[derive(Debug)]
 struct Race {
-    time: u64,
-    distance: u64,
+    time: BigUint,
+    distance: BigUint,
 }
 
 impl Race {
-    fn winning_times(&self) -> Vec<u64> {
-        // Your toy boat has a starting speed of zero millimeters per
-        // millisecond. For each whole millisecond you spend at the
-        // beginning of the race holding down the button, the boat's
-        // speed increases by one millimeter per millisecond.
-        let mut v = vec![];
-        for charge_time in 0..self.time {
-            if self.distance < (charge_time * (self.time - charge_time)) {
-                v.push(charge_time);
-            }
+    fn winning_count(&self, model: &BoatModel) -> Result<BigUint, WindowError> {
+        Ok(match model.winning_window(self)? {
+            Some((_, _, count)) => count,
+            None => BigUint::zero(),
+        })
+    }
+}
+
+/// How the digits on a sheet are read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kerning {
+    /// each column is its own race
+    Spaced,
+    /// the spaces are bad kerning, the whole line is one race
+    Kerned,
+}
+
+impl Kerning {
+    fn from_str(input: &str) -> Kerning {
+        match input {
+            "spaced" => Kerning::Spaced,
+            "kerned" => Kerning::Kerned,
+            _ => panic!("--kerning is spaced or kerned, not '{input}'"),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ParseError {
+    UnknownLine { line: usize, text: String },
+    DuplicateLabel { line: usize, label: String },
+    MissingLabel { sheet: usize, label: &'static str },
+    BadNumber { line: usize, text: String },
+    ColumnMismatch { sheet: usize, times: usize, distances: usize },
+    NoColumns { sheet: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownLine { line, text } =>
+                write!(f, "line {line}: expected 'Time:' or 'Distance:', got '{text}'"),
+            ParseError::DuplicateLabel { line, label } =>
+                write!(f, "line {line}: second '{label}:' line in the same sheet"),
+            ParseError::MissingLabel { sheet, label } =>
+                write!(f, "sheet {sheet}: no '{label}:' line"),
+            ParseError::BadNumber { line, text } =>
+                write!(f, "line {line}: '{text}' is not a number"),
+            ParseError::ColumnMismatch { sheet, times, distances } =>
+                write!(f, "sheet {sheet}: {times} times but {distances} distances"),
+            ParseError::NoColumns { sheet } =>
+                write!(f, "sheet {sheet}: no races, the lines have labels but no numbers"),
         }
-        return v;
     }
 }
 
 struct Day6 {}
 
 impl Day6 {
-    fn parse_input(input: &String) -> Vec<Race> {
-        let mut line_iter = input.lines();
+    /// Parses every race sheet in `input`. Sheets are separated by blank
+    /// lines and each has one `Time:` and one `Distance:` line in either
+    /// order.
+    fn parse_sheets(input: &str, kerning: Kerning) -> Result<Vec<Vec<Race>>, ParseError> {
+        let mut sheets = vec![];
+        let mut times = None;
+        let mut distances = None;
 
-        let times = num_str_to_vec(NUMBERS_RE.captures(line_iter.next().unwrap()).unwrap().get(1).unwrap().as_str());
-        let distances = num_str_to_vec(NUMBERS_RE.captures(line_iter.next().unwrap()).unwrap().get(1).unwrap().as_str());
+        // a trailing empty line flushes the final sheet
+        let lines = input.lines().chain(std::iter::once(""));
+        for (idx, l) in lines.enumerate() {
+            let line = idx + 1;
+            if l.trim().is_empty() {
+                if times.is_some() || distances.is_some() {
+                    let sheet = sheets.len() + 1;
+                    sheets.push(Day6::build_sheet(sheet, times.take(), distances.take(), kerning)?);
+                }
+                continue;
+            }
 
-        let mut races = vec![];
-        for idx in 0..(times.len()) {
-            races.push(Race{
-                time: *times.get(idx).unwrap(),
-                distance: *distances.get(idx).unwrap(),
-            });
+            let caps = LINE_RE.captures(l).ok_or_else(|| ParseError::UnknownLine {
+                line,
+                text: l.to_string(),
+            })?;
+            let label = caps.get(1).unwrap().as_str();
+            let target = match label {
+                "Time" => &mut times,
+                "Distance" => &mut distances,
+                _ => return Err(ParseError::UnknownLine { line, text: l.to_string() }),
+            };
+            if target.is_some() {
+                return Err(ParseError::DuplicateLabel { line, label: label.to_string() });
+            }
+            *target = Some(num_str_to_vec(caps.get(2).unwrap().as_str(), line)?);
         }
-        return races;
+        Ok(sheets)
     }
 
-    fn parse_input2(input: &String) -> Race {
-        let v = Day6::parse_input(input);
-        let mut time = String::new();
-        let mut dist = String::new();
-        for r in v {
-            time.push_str(r.time.to_string().as_str());
-            dist.push_str(r.distance.to_string().as_str());
+    fn build_sheet(
+        sheet: usize,
+        times: Option<Vec<String>>,
+        distances: Option<Vec<String>>,
+        kerning: Kerning,
+    ) -> Result<Vec<Race>, ParseError> {
+        let times = times.ok_or(ParseError::MissingLabel { sheet, label: "Time" })?;
+        let distances = distances.ok_or(ParseError::MissingLabel { sheet, label: "Distance" })?;
+        if times.len() != distances.len() {
+            return Err(ParseError::ColumnMismatch {
+                sheet,
+                times: times.len(),
+                distances: distances.len(),
+            });
+        }
+        if times.is_empty() {
+            return Err(ParseError::NoColumns { sheet });
         }
 
-        Race { time: time.parse().expect("num"), distance: dist.parse().expect("num") }
+        let races = match kerning {
+            Kerning::Spaced => times.iter().zip(distances.iter())
+                .map(|(time, distance)| Race {
+                    time: time.parse().expect("digits"),
+                    distance: distance.parse().expect("digits"),
+                })
+                .collect(),
+            Kerning::Kerned => vec![Race {
+                time: times.concat().parse().expect("digits"),
+                distance: distances.concat().parse().expect("digits"),
+            }],
+        };
+        Ok(races)
     }
 }
 
-fn num_str_to_vec(input: &str) -> Vec<u64> {
+/// Splits a line of numbers into its digit strings. They stay as strings
+/// until we know whether the columns are kerned together.
+fn num_str_to_vec(input: &str, line: usize) -> Result<Vec<String>, ParseError> {
     let mut r = Vec::new();
-    for e in input.split(" ") {
-        if e.len() == 0 {
+    for e in input.split(' ') {
+        let e = e.trim();
+        if e.is_empty() {
             continue;
         }
-        r.push(e.trim().parse().expect("a number"));
+        if !e.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseError::BadNumber { line, text: e.to_string() });
+        }
+        r.push(e.to_string());
     }
-    return r;
+    Ok(r)
 }
 
-
-fn part1(input: &String) {
-    let races = Day6::parse_input(input);
-
-    let mut acc = 1;
+fn winnings(races: &[Race], model: &BoatModel) -> Result<BigUint, WindowError> {
+    let mut acc = BigUint::one();
 
     for r in races {
         // println!("{:?}", r);
-        let wt = r.winning_times();
-        // println!("{:?}", wt);
-        acc = acc * wt.len();
+        acc *= r.winning_count(model)?;
+    }
+    Ok(acc)
+}
+
+fn winnings_or_exit(races: &[Race], model: &BoatModel) -> BigUint {
+    winnings(races, model).unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    })
+}
+
+fn parse_or_exit(input: &str, kerning: Kerning) -> Vec<Vec<Race>> {
+    match Day6::parse_sheets(input, kerning) {
+        Ok(sheets) => sheets,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        },
     }
+}
 
-    println!("acc: {acc}");
+fn part1(input: &str, model: &BoatModel) {
+    for races in parse_or_exit(input, Kerning::Spaced) {
+        let acc = winnings_or_exit(&races, model);
+        println!("acc: {acc}");
+    }
 }
 
-fn part2(input: &String) {
-    let r = Day6::parse_input2(input);
-    let wt = r.winning_times();
-    let acc = wt.len();
-    println!("acc: {acc}");
+fn part2(input: &str, model: &BoatModel) {
+    for races in parse_or_exit(input, Kerning::Kerned) {
+        let acc = winnings_or_exit(&races, model);
+        println!("acc: {acc}");
+    }
+}
+
+/// Removes `flag` and the value following it from `args`.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let idx = args.iter().position(|a| a == flag)?;
+    args.remove(idx);
+    if idx >= args.len() {
+        panic!("{flag} needs a value");
+    }
+    Some(args.remove(idx))
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let explore = args.first().is_some_and(|a| a == "explore");
+    if explore {
+        args.remove(0);
+    }
+
+    // day6 [explore] [input] [--kerning spaced|kerned] [--plot DIR]
+    //      [--rate N] [--top-speed N] [--decay N] [--min-charge N]
+    //      [--max-charge N]
+    let kerning = take_flag(&mut args, "--kerning").map(|k| Kerning::from_str(&k));
+    let plot_dir = take_flag(&mut args, "--plot");
+    let model = BoatModel::from_args(&mut args);
+
     let default_path = String::from("test.txt");
-    let fp = args.get(1).unwrap_or(&default_path);
+    let fp = args.first().unwrap_or(&default_path);
 
     let input = std::fs::read_to_string(fp).expect("input");
-    part1(&input);
-    part2(&input);
+    if explore {
+        for (sheet, races) in parse_or_exit(&input, kerning.unwrap_or(Kerning::Spaced)).iter().enumerate() {
+            explore::explore(sheet + 1, races, &model, plot_dir.as_deref().map(Path::new));
+        }
+        return;
+    }
+    match kerning {
+        Some(Kerning::Spaced) => part1(&input, &model),
+        Some(Kerning::Kerned) => part2(&input, &model),
+        None => {
+            part1(&input, &model);
+            part2(&input, &model);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(input: &str) -> ParseError {
+        Day6::parse_sheets(input, Kerning::Spaced).unwrap_err()
+    }
+
+    #[test]
+    fn column_mismatch() {
+        assert!(matches!(
+            parse_error("Time: 7 15 30\nDistance: 9 40\n"),
+            ParseError::ColumnMismatch { sheet: 1, times: 3, distances: 2 }
+        ));
+    }
+
+    #[test]
+    fn duplicate_label() {
+        let e = parse_error("Time: 7\nDistance: 9\n\nTime: 15\nTime: 30\nDistance: 40\n");
+        assert!(matches!(e, ParseError::DuplicateLabel { line: 5, ref label } if label == "Time"));
+    }
+
+    #[test]
+    fn missing_label() {
+        assert!(matches!(parse_error("Time: 7 15\n"), ParseError::MissingLabel { sheet: 1, label: "Distance" }));
+        assert!(matches!(
+            parse_error("Time: 7\nDistance: 9\n\nDistance: 40\n"),
+            ParseError::MissingLabel { sheet: 2, label: "Time" }
+        ));
+    }
+
+    #[test]
+    fn no_columns() {
+        for kerning in [Kerning::Spaced, Kerning::Kerned] {
+            let e = Day6::parse_sheets("Time:\nDistance:\n", kerning).unwrap_err();
+            assert!(matches!(e, ParseError::NoColumns { sheet: 1 }));
+        }
+    }
+
+    #[test]
+    fn kerned_past_u64() {
+        let input = "Time:      2000000000000000 000000000000000\n\
+                     Distance:  996189605311690290812376162109 489407092136869378932479804863\n";
+        let sheets = Day6::parse_sheets(input, Kerning::Kerned).unwrap();
+        let count: BigUint = "123456789012345678901234567889".parse().unwrap();
+        assert_eq!(winnings(&sheets[0], &BoatModel::default()).unwrap(), count);
+    }
 }
@@ -1,222 +1,365 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::env;
-use std::ops::Index;
+use std::fmt;
 
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rules::Ruleset;
+
+mod report;
+mod rules;
+mod simulate;
+mod verify;
+
+#[derive(Debug, PartialEq)]
+enum HandError {
+    Empty,
+    UnknownLabel { label: char, ruleset: String },
+    BadWager { text: String },
+    MixedSizes { expected: usize, got: usize },
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandError::Empty =>
+                write!(f, "hand has no cards"),
+            HandError::UnknownLabel { label, ruleset } =>
+                write!(f, "'{label}' isn't a {ruleset} card"),
+            HandError::BadWager { text } =>
+                write!(f, "'{text}' isn't a wager"),
+            HandError::MixedSizes { expected, got } =>
+                write!(f, "hand has {got} cards but earlier hands have {expected}"),
+        }
+    }
+}
+
+/// A `HandError` and the input line it came from.
 #[derive(Debug)]
-enum HandType {
-    FiveOfAKind,
-    FourOfAKind,
-    FullHouse,
-    ThreeOfAKind,
-    TwoPair,
-    OnePair,
-    HighCard,
+struct LineError {
+    line: usize,
+    error: HandError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+/// The shape of a hand: how many cards share each label, largest group
+/// first. For five cards this is the puzzle's seven types, e.g. a full house
+/// is `[3, 2]` and two pair is `[2, 2, 1]`, but any hand size works and
+/// comparing shapes group by group ranks them the same way the puzzle does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct HandType {
+    counts: Vec<u32>,
 }
 
 impl HandType {
-    fn from_str(input: &String, jokers_wild: bool) -> HandType {
+    /// Classifies `input`, which must be non-empty and only use labels from
+    /// `ruleset`. Wild cards all join the strongest group.
+    fn from_str(input: &str, ruleset: &Ruleset) -> Result<HandType, HandError> {
         // println!("input: {}", input);
-        if input == "JJJJJ" {
-            return HandType::FiveOfAKind;
+        if input.is_empty() {
+            return Err(HandError::Empty);
+        }
+        if let Some(label) = input.chars().find(|c| ruleset.card_points(*c).is_none()) {
+            return Err(HandError::UnknownLabel { label, ruleset: ruleset.name.clone() });
         }
+
         // card -> count
-        let mut m: HashMap<char, i32> = HashMap::new();
+        let mut m: HashMap<char, u32> = HashMap::new();
 
         // holds current strongest card
-        let mut strongest_card = ('A', -1);
-
-        for card in input.as_bytes() {
-            let card_c = char::from(*card);
-            if m.contains_key(&card_c) {
-                 m.insert(card_c, m.get(&card_c).unwrap() + 1);
-            } else {
-                m.insert(card_c, 1);
-            }
+        let mut strongest_card = None;
 
-            if let Some(card_count) = m.get(&card_c) {
-                if *card_count >= strongest_card.1 && card_c != 'J' {
-                    strongest_card = (card_c, *card_count);
-                }
+        for card_c in input.chars() {
+            let card_count = m.entry(card_c).or_insert(0);
+            *card_count += 1;
+
+            if ruleset.is_wild(card_c) {
+                continue;
+            }
+            match strongest_card {
+                Some((_, count)) if *card_count < count => {},
+                _ => strongest_card = Some((card_c, *card_count)),
             }
         }
 
-        if jokers_wild {
-            // reassign jokers to the strongest card
-            if let Some(joker_count) = m.get(&'J') {
-                let cur = m.get(&strongest_card.0).unwrap();
-                m.insert(strongest_card.0, *cur + joker_count);
-                // not strictly required but do it anyway
-                m.remove(&'J');
-            }
+        // reassign wild cards to the strongest card
+        let mut wild_count = 0;
+        for wild in ruleset.wild.iter() {
+            // not strictly required but do it anyway
+            wild_count += m.remove(wild).unwrap_or(0);
+        }
+        match strongest_card {
+            Some((card_c, _)) => *m.get_mut(&card_c).unwrap() += wild_count,
+            // every card is wild, they all match each other
+            None => { m.insert(input.chars().next().unwrap(), wild_count); },
         }
 
         // println!("m: {:?}", m);
 
-        // count -> counts
-        let mut card_counts = HashMap::new();
-        for count in 0..6 {
-            for key in m.keys() {
-                // jokers will be a special case
-                if jokers_wild && *key == 'J' {
-                    continue;
-                }
-                let key_count = *m.get(key).unwrap();
-                if key_count == count {
-                    if card_counts.contains_key(&count) {
-                        let cur = *card_counts.get(&count).unwrap();
-                        card_counts.insert(count, cur + 1);
-                    } else {
-                        card_counts.insert(count, 1);
-                    }
-                }
-            }
-        }
+        let mut counts: Vec<u32> = m.into_values().collect();
+        counts.sort_by(|a, b| b.cmp(a));
+        Ok(HandType { counts })
+    }
 
-        // println!("card_counts: {:?}", card_counts);
+    /// The puzzle's name for this shape, or the group sizes joined with
+    /// `+` for shapes it doesn't name.
+    fn name(&self) -> String {
+        let groups: Vec<u32> = self.counts.iter().copied().filter(|c| *c > 1).collect();
+        let name = match groups.as_slice() {
+            [] => "HighCard",
+            [2] => "OnePair",
+            [2, 2] => "TwoPair",
+            [3] => "ThreeOfAKind",
+            [3, 2] => "FullHouse",
+            [4] => "FourOfAKind",
+            [5] => "FiveOfAKind",
+            _ => {
+                let parts: Vec<String> = groups.iter().map(|c| c.to_string()).collect();
+                return parts.join("+");
+            },
+        };
+        String::from(name)
+    }
+}
 
-        if *card_counts.get(&5).unwrap_or(&0) == 1 {
-            return HandType::FiveOfAKind;
-        }
-        if *card_counts.get(&4).unwrap_or(&0) == 1 {
-            return HandType::FourOfAKind;
-        }
-        if *card_counts.get(&3).unwrap_or(&0) == 1 {
-            if *card_counts.get(&2).unwrap_or(&0) == 1 {
-                return HandType::FullHouse;
-            }
-            if *card_counts.get(&1).unwrap_or(&0) == 2 {
-                return HandType::ThreeOfAKind;
-            }
-        }
-        if *card_counts.get(&2).unwrap_or(&0) == 2 {
-            return HandType::TwoPair;
-        }
-        if *card_counts.get(&2).unwrap_or(&0) == 1 {
-            return HandType::OnePair;
-        }
-        if *card_counts.get(&1).unwrap_or(&0) == 5 {
-            return HandType::HighCard;
-        }
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
 
-        panic!("Shouldn't be here: {}", input);
+impl PartialOrd for HandType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
+}
 
-    fn points(&self) -> u32 {
-        match self {
-            HandType::FiveOfAKind => 7,
-            HandType::FourOfAKind => 6,
-            HandType::FullHouse => 5,
-            HandType::ThreeOfAKind => 4,
-            HandType::TwoPair => 3,
-            HandType::OnePair => 2,
-            HandType::HighCard => 1,
-        }
+impl Ord for HandType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // bigger groups beat smaller ones, the first group that differs
+        // decides; Vec's ordering does exactly that
+        self.counts.cmp(&other.counts)
     }
 }
 
+/// A hand of cards, ordered weakest to strongest. Hands are only meant to be
+/// compared against hands dealt under the same ruleset.
 #[derive(Debug)]
-struct Hand {
+struct Hand<'a> {
     typ: HandType,
     wager: u32,
     cards: Vec<char>,
+    ruleset: &'a Ruleset,
 }
 
-impl Hand {
-    fn from_str(input: &String, jokers_wild: bool) -> Hand {
-        let mut parts = input.split(" ");
-        let cards = parts.next().unwrap();
-        let wager = parts.next().unwrap().parse().expect("number");
-        let mut card_vec: Vec<char> = cards.chars().collect();
+impl<'a> Hand<'a> {
+    fn from_str(input: &str, ruleset: &'a Ruleset) -> Result<Hand<'a>, HandError> {
+        let mut parts = input.split_whitespace();
+        let cards = parts.next().ok_or(HandError::Empty)?;
+        let wager_str = parts.next().unwrap_or("");
+        let wager = wager_str.parse()
+            .map_err(|_| HandError::BadWager { text: wager_str.to_string() })?;
+        Hand::from_cards(cards, wager, ruleset)
+    }
+
+    fn from_cards(cards: &str, wager: u32, ruleset: &'a Ruleset) -> Result<Hand<'a>, HandError> {
+        let card_vec: Vec<char> = cards.chars().collect();
         // card_vec.sort_by(|a, b| card_points(*b).cmp(&card_points(*a)));
 
-        Hand {
-            typ: HandType::from_str(&String::from(cards), jokers_wild),
+        Ok(Hand {
+            typ: HandType::from_str(cards, ruleset)?,
             wager,
             cards: card_vec,
-        }
+            ruleset,
+        })
+    }
+
+    fn card_points(&self, idx: usize) -> u32 {
+        let card = self.cards[idx];
+        self.ruleset.card_points(card)
+            .unwrap_or_else(|| panic!("'{card}' isn't a {} card", self.ruleset.name))
     }
 }
 
-fn card_points(input: char) -> u32 {
-    let cards = "AKQT98765432J";
-    let max_value = cards.len();
-    let r = cards.find(|x| x == input).unwrap();
-    return (max_value - r) as u32;
+// equal means equally strong, the wager doesn't come into it
+impl PartialEq for Hand<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
 }
 
-fn part1(input: &String) {
-    // println!("{}\n\n", input);
-    let mut hands: Vec<Hand> = input.lines()
-        .map(|line| line.trim())
-        .filter(|line| line.len() != 0)
-        .map(|line| Hand::from_str(&line.to_string(), false))
-        .collect();
-
-    hands.sort_by(|a, b| {
-        let a_points = a.typ.points();
-        let b_points = b.typ.points();
-        if a_points != b_points {
-            // return b_points.cmp(&a_points);
-            return a_points.cmp(&b_points);
-        } else {
-            let a_hand = &a.cards;
-            let b_hand = &b.cards;
-            for card_idx in 0..5 {
-                let a_card = a_hand.index(card_idx);
-                let b_card = b_hand.index(card_idx);
-                if *a_card != *b_card {
-                    return card_points(*a_card).cmp(&card_points(*b_card));
+impl Eq for Hand<'_> {}
+
+impl PartialOrd for Hand<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.typ.cmp(&other.typ).then_with(|| {
+            let hand_size = self.cards.len().min(other.cards.len());
+            for idx in self.ruleset.tie_break.positions(hand_size) {
+                if self.cards[idx] != other.cards[idx] {
+                    return self.card_points(idx).cmp(&other.card_points(idx));
                 }
             }
+            Ordering::Equal
+        })
+    }
+}
+
+/// Parses one hand per line, every hand must be the same size.
+fn parse_hands<'a>(input: &str, ruleset: &'a Ruleset) -> Result<Vec<Hand<'a>>, LineError> {
+    let mut hands: Vec<Hand> = vec![];
+    for (idx, l) in input.lines().enumerate() {
+        let line = l.trim();
+        if line.is_empty() {
+            continue;
         }
-        return Ordering::Equal;
-    });
+        let hand = Hand::from_str(line, ruleset)
+            .map_err(|error| LineError { line: idx + 1, error })?;
+        if let Some(first) = hands.first() {
+            if first.cards.len() != hand.cards.len() {
+                let error = HandError::MixedSizes { expected: first.cards.len(), got: hand.cards.len() };
+                return Err(LineError { line: idx + 1, error });
+            }
+        }
+        hands.push(hand);
+    }
+    Ok(hands)
+}
+
+fn or_exit<T, E: fmt::Display>(r: Result<T, E>) -> T {
+    match r {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        },
+    }
+}
 
+fn total_winnings(hands: &[Hand]) -> u32 {
     let mut acc = 0;
-    for rank in 0..hands.len() {
-        // println!("{} * {}", rank+1, hands.index(rank).wager);
-        acc = acc + (
-            ((rank as u32)+1) * hands.index(rank).wager);
+    for (rank, hand) in hands.iter().enumerate() {
+        // println!("{} * {}", rank+1, hand.wager);
+        acc += ((rank as u32) + 1) * hand.wager;
     }
+    acc
+}
+
+fn score(input: &str, ruleset: &Ruleset) {
+    // println!("{}\n\n", input);
+    let mut hands = or_exit(parse_hands(input, ruleset));
+    hands.sort();
 
+    let acc = total_winnings(&hands);
     println!("acc: {acc}");
 }
 
-fn part2(input: &String) {
-    let mut hands: Vec<Hand> = input.lines()
-        .map(|line| line.trim())
-        .filter(|line| line.len() != 0)
-        .map(|line| Hand::from_str(&line.to_string(), true))
-        .collect();
-
-    hands.sort_by(|a, b| {
-        let a_points = a.typ.points();
-        let b_points = b.typ.points();
-        if a_points != b_points {
-            // return b_points.cmp(&a_points);
-            return a_points.cmp(&b_points);
-        } else {
-            let a_hand = &a.cards;
-            let b_hand = &b.cards;
-            for card_idx in 0..5 {
-                let a_card = a_hand.index(card_idx);
-                let b_card = b_hand.index(card_idx);
-                if *a_card != *b_card {
-                    return card_points(*a_card).cmp(&card_points(*b_card));
-                }
-            }
-        }
-        return Ordering::Equal;
-    });
+fn report(input: &str, ruleset: &Ruleset, csv: bool) {
+    let mut hands = or_exit(parse_hands(input, ruleset));
+    hands.sort();
 
-    let mut acc = 0;
-    for rank in 0..hands.len() {
-        acc = acc + (
-            ((rank as u32)+1) * hands.index(rank).wager);
+    let rows = report::rows(&hands, ruleset);
+    if csv {
+        report::print_csv(&rows);
+    } else {
+        println!("{ruleset}");
+        report::print_table(&rows);
     }
+}
 
-    println!("acc: {acc}");
+fn part1(input: &str) {
+    score(input, &Ruleset::standard());
+}
+
+fn part2(input: &str) {
+    score(input, &Ruleset::jokers());
+}
+
+fn compare(a: &str, b: &str, ruleset: &Ruleset) {
+    let a_hand = or_exit(Hand::from_cards(a, 0, ruleset));
+    let b_hand = or_exit(Hand::from_cards(b, 0, ruleset));
+    println!("{a}: {}", a_hand.typ);
+    println!("{b}: {}", b_hand.typ);
+    match a_hand.cmp(&b_hand) {
+        Ordering::Greater => println!("{a} beats {b}"),
+        Ordering::Less => println!("{b} beats {a}"),
+        Ordering::Equal => println!("{a} and {b} tie"),
+    }
+}
+
+fn simulate(mut args: Vec<String>, ruleset: Ruleset) {
+    let parse = |flag: &str, v: String| -> usize {
+        v.parse().unwrap_or_else(|_| panic!("{flag} number"))
+    };
+    let seed = take_flag(&mut args, "--seed").map(|v| v.parse().expect("--seed number"));
+    let tournaments = take_flag(&mut args, "--tournaments").map_or(1000, |v| parse("--tournaments", v));
+    let hand_size = take_flag(&mut args, "--hand-size").map_or(5, |v| parse("--hand-size", v));
+    let copies = take_flag(&mut args, "--copies").map_or(4, |v| parse("--copies", v));
+    let jokers = take_flag(&mut args, "--joker-count").map_or(copies, |v| parse("--joker-count", v));
+    let replacement = !take_switch(&mut args, "--no-replacement");
+    let bids = take_flag(&mut args, "--bids");
+    let mut challengers = vec![];
+    while let Some(hand) = take_flag(&mut args, "--vs") {
+        challengers.push(hand);
+    }
+
+    // without --bids, play the bids from the input with freshly dealt hands
+    let bids: Vec<u32> = match bids {
+        Some(list) => list.split(',').map(|b| b.trim().parse().expect("--bids numbers")).collect(),
+        None => {
+            let default_path = String::from("test.txt");
+            let fp = args.first().unwrap_or(&default_path);
+            let input = std::fs::read_to_string(fp).expect("input");
+            or_exit(parse_hands(&input, &ruleset)).iter().map(|h| h.wager).collect()
+        },
+    };
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("{ruleset}, seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let sim = simulate::Simulation {
+        ruleset,
+        deck: simulate::Deck { copies, jokers, replacement },
+        hand_size,
+        tournaments,
+        bids,
+        challengers,
+    };
+    let results = or_exit(sim.run(&mut rng));
+    simulate::print_results(&sim, &results);
+}
+
+/// Removes `flag` and the value following it from `args`.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let idx = args.iter().position(|a| a == flag)?;
+    args.remove(idx);
+    if idx >= args.len() {
+        panic!("{flag} needs a value");
+    }
+    Some(args.remove(idx))
+}
+
+/// Removes `flag` from `args`, returning whether it was there.
+fn take_switch(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|a| a == flag) {
+        Some(idx) => {
+            args.remove(idx);
+            true
+        },
+        None => false,
+    }
 }
 
 fn main() {
@@ -226,11 +369,116 @@ fn main() {
     // println!("J -> {}", card_points(b'J'));
     // println!("T -> {}", card_points(b'T'));
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // --rules takes a ruleset name (standard, jokers) or a ruleset file,
+    // --jokers is short for --rules jokers
+    let mut ruleset = None;
+    if take_switch(&mut args, "--jokers") {
+        ruleset = Some(Ruleset::jokers());
+    }
+    if let Some(name) = take_flag(&mut args, "--rules") {
+        ruleset = Some(or_exit(Ruleset::load(&name)));
+    }
+
+    // day7 compare <hand> <hand> [--rules <name|file>]
+    if args.first().is_some_and(|a| a == "compare") {
+        if args.len() != 3 {
+            panic!("usage: day7 compare <hand> <hand> [--rules <name|file>]");
+        }
+        compare(&args[1], &args[2], &ruleset.unwrap_or_else(Ruleset::standard));
+        return;
+    }
+
+    // day7 verify [--rules <name|file>] [--size N]
+    if args.first().is_some_and(|a| a == "verify") {
+        let hand_size = take_flag(&mut args, "--size").map_or(5, |v| v.parse().expect("--size number"));
+        let ruleset = ruleset.unwrap_or_else(Ruleset::jokers);
+        let (checked, bad) = verify::verify(&ruleset, hand_size);
+        for d in bad.iter() {
+            println!("{}: heuristic {} but best is {}", d.cards, d.heuristic, d.brute_force);
+        }
+        println!("{ruleset}: checked {checked} hands, {} discrepancies", bad.len());
+        return;
+    }
+
+    // day7 simulate [input] [--rules <name|file>] [--seed N]
+    //      [--tournaments N] [--hand-size N] [--copies N] [--joker-count N]
+    //      [--no-replacement] [--bids 1,2,3] [--vs <hand>]...
+    if args.first().is_some_and(|a| a == "simulate") {
+        args.remove(0);
+        simulate(args, ruleset.unwrap_or_else(Ruleset::jokers));
+        return;
+    }
+
+    // day7 report [input] [--rules <name|file>] [--csv]
+    let report_mode = args.first().is_some_and(|a| a == "report");
+    let mut csv = false;
+    if report_mode {
+        args.remove(0);
+        csv = take_switch(&mut args, "--csv");
+    }
+
+    // day7 [input] [--rules <name|file>]
     let default_path = String::from("test.txt");
-    let fp = args.get(1).unwrap_or(&default_path);
+    let fp = args.first().unwrap_or(&default_path);
 
     let input = std::fs::read_to_string(fp).expect("input");
-    part1(&input);
-    part2(&input);
+    if report_mode {
+        match ruleset {
+            Some(r) => report(&input, &r, csv),
+            None => {
+                report(&input, &Ruleset::standard(), csv);
+                if !csv {
+                    println!();
+                }
+                report(&input, &Ruleset::jokers(), csv);
+            },
+        }
+        return;
+    }
+    match ruleset {
+        Some(r) => {
+            println!("{r}");
+            score(&input, &r);
+        },
+        None => {
+            part1(&input);
+            part2(&input);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typ(cards: &str, ruleset: &Ruleset) -> Vec<u32> {
+        HandType::from_str(cards, ruleset).unwrap().counts
+    }
+
+    #[test]
+    fn six_card_shapes() {
+        let standard = Ruleset::standard();
+        assert_eq!(typ("KKKQQQ", &standard), vec![3, 3]);
+        assert_eq!(typ("AAJJJJ", &Ruleset::jokers()), vec![6]);
+
+        let two_triples = Hand::from_cards("KKKQQQ", 1, &standard).unwrap();
+        let four_aces = Hand::from_cards("AAAAKQ", 1, &standard).unwrap();
+        assert!(two_triples < four_aces);
+    }
+
+    #[test]
+    fn hand_errors() {
+        let standard = Ruleset::standard();
+        assert_eq!(Hand::from_cards("", 1, &standard).unwrap_err(), HandError::Empty);
+
+        let e = parse_hands("32T3K 765\nT55X5 684\n", &standard).unwrap_err();
+        assert_eq!(e.line, 2);
+        assert_eq!(e.error, HandError::UnknownLabel { label: 'X', ruleset: String::from("standard") });
+
+        let e = parse_hands("32T3K 765\n\nT55J 684\n", &standard).unwrap_err();
+        assert_eq!(e.line, 3);
+        assert_eq!(e.error, HandError::MixedSizes { expected: 5, got: 4 });
+    }
 }
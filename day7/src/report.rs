@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use crate::rules::Ruleset;
+use crate::{Hand, HandType};
+
+/// One line of the ranking report.
+pub struct Row {
+    pub rank: u32,
+    pub cards: String,
+    /// type with wild cards counted as themselves
+    pub plain: HandType,
+    /// type after wild cards are substituted, what ranking uses
+    pub typ: HandType,
+    pub wager: u32,
+    pub winnings: u32,
+}
+
+/// Rows for `hands`, which must already be sorted weakest first.
+pub fn rows(hands: &[Hand], ruleset: &Ruleset) -> Vec<Row> {
+    let plain_rules = ruleset.without_wild();
+    hands.iter().enumerate().map(|(idx, hand)| {
+        let cards: String = hand.cards.iter().collect();
+        let rank = (idx as u32) + 1;
+        Row {
+            rank,
+            plain: HandType::from_str(&cards, &plain_rules).expect("already parsed"),
+            cards,
+            typ: hand.typ.clone(),
+            wager: hand.wager,
+            winnings: rank * hand.wager,
+        }
+    }).collect()
+}
+
+pub fn print_table(rows: &[Row]) {
+    println!("{:>6}  {:<8} {:<14} {:<14} {:>6} {:>10}", "rank", "cards", "type", "wild type", "bid", "winnings");
+    for r in rows {
+        println!("{:>6}  {:<8} {:<14} {:<14} {:>6} {:>10}",
+            r.rank, r.cards, r.plain.name(), r.typ.name(), r.wager, r.winnings);
+    }
+    let total: u32 = rows.iter().map(|r| r.winnings).sum();
+    println!("total winnings: {total}");
+
+    println!();
+    println!("{:<14} {:>6}", "type", "hands");
+    for (typ, count) in summary(rows).iter().rev() {
+        println!("{:<14} {:>6}", typ.name(), count);
+    }
+}
+
+pub fn print_csv(rows: &[Row]) {
+    println!("rank,cards,type,wild_type,bid,winnings");
+    for r in rows {
+        println!("{},{},{},{},{},{}", r.rank, r.cards, r.plain.name(), r.typ.name(), r.wager, r.winnings);
+    }
+}
+
+/// How many hands ended up as each type, after wild cards.
+pub fn summary(rows: &[Row]) -> BTreeMap<HandType, usize> {
+    let mut m = BTreeMap::new();
+    for r in rows {
+        *m.entry(r.typ.clone()).or_insert(0) += 1;
+    }
+    m
+}
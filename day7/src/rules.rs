@@ -0,0 +1,195 @@
+use std::fmt;
+
+/// Order in which card positions are compared when two hands share a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TieBreak {
+    LeftToRight,
+    RightToLeft,
+    /// compare only these positions (0 is the first card), in this order
+    Positions(Vec<usize>),
+}
+
+impl TieBreak {
+    pub fn positions(&self, hand_size: usize) -> Vec<usize> {
+        match self {
+            TieBreak::LeftToRight => (0..hand_size).collect(),
+            TieBreak::RightToLeft => (0..hand_size).rev().collect(),
+            TieBreak::Positions(v) => v.iter().copied().filter(|p| *p < hand_size).collect(),
+        }
+    }
+}
+
+/// Everything that changes between Camel Cards variants.
+///
+/// A ruleset file is `key = value` lines, `#` starts a comment:
+///
+/// ```text
+/// name = jokers
+/// order = AKQT98765432J
+/// wild = J
+/// tiebreak = left-to-right
+/// ```
+///
+/// `order` lists the labels strongest first, `wild` is optional and
+/// `tiebreak` is `left-to-right` (the default), `right-to-left`, or a space
+/// separated list of card positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+    pub name: String,
+    /// labels, strongest first
+    pub order: Vec<char>,
+    /// labels that stand in for whatever makes the hand strongest
+    pub wild: Vec<char>,
+    pub tie_break: TieBreak,
+}
+
+impl Ruleset {
+    /// Part 1 rules: J is a jack and ranks between Q and T.
+    pub fn standard() -> Ruleset {
+        Ruleset {
+            name: String::from("standard"),
+            order: "AKQJT98765432".chars().collect(),
+            wild: vec![],
+            tie_break: TieBreak::LeftToRight,
+        }
+    }
+
+    /// Part 2 rules: J is a joker, wild for typing and the weakest label
+    /// for tie breaks.
+    pub fn jokers() -> Ruleset {
+        Ruleset {
+            name: String::from("jokers"),
+            order: "AKQT98765432J".chars().collect(),
+            wild: vec!['J'],
+            tie_break: TieBreak::LeftToRight,
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Ruleset> {
+        match name {
+            "standard" => Some(Ruleset::standard()),
+            "jokers" => Some(Ruleset::jokers()),
+            _ => None,
+        }
+    }
+
+    /// A built in ruleset by name, otherwise a ruleset file at that path.
+    pub fn load(name_or_path: &str) -> Result<Ruleset, String> {
+        if let Some(r) = Ruleset::by_name(name_or_path) {
+            return Ok(r);
+        }
+        let input = std::fs::read_to_string(name_or_path)
+            .map_err(|e| format!("{name_or_path}: not a ruleset name and can't read it: {e}"))?;
+        Ruleset::from_str(&input)
+    }
+
+    pub fn from_str(input: &str) -> Result<Ruleset, String> {
+        let mut name = String::from("custom");
+        let mut order = None;
+        let mut wild = vec![];
+        let mut tie_break = TieBreak::LeftToRight;
+
+        for (idx, l) in input.lines().enumerate() {
+            let line = l.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected 'key = value'", idx + 1))?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => name = value.to_string(),
+                "order" => order = Some(value.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>()),
+                "wild" => wild = value.chars().filter(|c| !c.is_whitespace()).collect(),
+                "tiebreak" => tie_break = match value {
+                    "left-to-right" => TieBreak::LeftToRight,
+                    "right-to-left" => TieBreak::RightToLeft,
+                    _ => TieBreak::Positions(value.split_whitespace()
+                        .map(|p| p.parse().map_err(|_| format!("line {}: bad position '{p}'", idx + 1)))
+                        .collect::<Result<_, _>>()?),
+                },
+                other => return Err(format!("line {}: unknown key '{other}'", idx + 1)),
+            }
+        }
+
+        let order = order.ok_or("ruleset needs an 'order' line")?;
+        for (idx, c) in order.iter().enumerate() {
+            if order[..idx].contains(c) {
+                return Err(format!("'{c}' appears twice in order"));
+            }
+        }
+        if let Some(c) = wild.iter().find(|c| !order.contains(c)) {
+            return Err(format!("wild label '{c}' isn't in order"));
+        }
+
+        Ok(Ruleset { name, order, wild, tie_break })
+    }
+
+    /// The same rules with every card counted as itself.
+    pub fn without_wild(&self) -> Ruleset {
+        Ruleset {
+            wild: vec![],
+            ..self.clone()
+        }
+    }
+
+    pub fn is_wild(&self, label: char) -> bool {
+        self.wild.contains(&label)
+    }
+
+    /// Strength of a label for tie breaks, higher is stronger.
+    pub fn card_points(&self, label: char) -> Option<u32> {
+        let r = self.order.iter().position(|c| *c == label)?;
+        Some((self.order.len() - r) as u32)
+    }
+}
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let order: String = self.order.iter().collect();
+        let wild: String = self.wild.iter().collect();
+        write!(f, "{} (order {order}, wild '{wild}')", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_file() {
+        let r = Ruleset::from_str("# aces low\nname = low\norder = KQJT98765432A  # spaces allowed\n\nwild = 2\ntiebreak = 4 0\n").unwrap();
+        assert_eq!(r.name, "low");
+        assert_eq!(r.order, "KQJT98765432A".chars().collect::<Vec<char>>());
+        assert_eq!(r.wild, vec!['2']);
+        assert_eq!(r.tie_break, TieBreak::Positions(vec![4, 0]));
+        assert_eq!(r.tie_break.positions(3), vec![0]);
+    }
+
+    #[test]
+    fn tiebreak_directions() {
+        let r = Ruleset::from_str("order = AK\ntiebreak = right-to-left").unwrap();
+        assert_eq!(r.tie_break.positions(3), vec![2, 1, 0]);
+        assert_eq!(Ruleset::from_str("order = AK").unwrap().tie_break.positions(3), vec![0, 1, 2]);
+        assert_eq!(Ruleset::from_str("order = AK\ntiebreak = 1 x").unwrap_err(), "line 2: bad position 'x'");
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        assert_eq!(Ruleset::from_str("order = AKQA").unwrap_err(), "'A' appears twice in order");
+        assert_eq!(Ruleset::from_str("order = AKQ\nwild = J").unwrap_err(), "wild label 'J' isn't in order");
+        assert_eq!(Ruleset::from_str("order = AKQ\njokers = J").unwrap_err(), "line 2: unknown key 'jokers'");
+        assert_eq!(Ruleset::from_str("name = empty").unwrap_err(), "ruleset needs an 'order' line");
+    }
+
+    #[test]
+    fn standard_jack_between_queen_and_ten() {
+        let r = Ruleset::standard();
+        assert!(r.card_points('Q') > r.card_points('J'));
+        assert!(r.card_points('J') > r.card_points('T'));
+
+        let mut hands = crate::parse_hands(include_str!("../test.txt"), &r).unwrap();
+        hands.sort();
+        assert_eq!(crate::total_winnings(&hands), 6440);
+    }
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::rules::Ruleset;
+use crate::{Hand, HandType};
+
+/// Where dealt cards come from.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    /// copies of each ordinary label
+    pub copies: usize,
+    /// copies of each wild label
+    pub jokers: usize,
+    /// put every card back after dealing it, so any hand is possible
+    pub replacement: bool,
+}
+
+impl Deck {
+    fn cards(&self, ruleset: &Ruleset) -> Vec<char> {
+        let mut cards = vec![];
+        for label in ruleset.order.iter() {
+            let n = if ruleset.is_wild(*label) { self.jokers } else { self.copies };
+            cards.extend(std::iter::repeat_n(*label, n));
+        }
+        cards
+    }
+}
+
+pub struct Simulation {
+    pub ruleset: Ruleset,
+    pub deck: Deck,
+    pub hand_size: usize,
+    pub tournaments: usize,
+    /// one bid per hand in each tournament
+    pub bids: Vec<u32>,
+    /// hands to play against randomly dealt opponents
+    pub challengers: Vec<String>,
+}
+
+/// Win, tie and loss counts for one challenger.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HeadToHead {
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Results {
+    pub types: BTreeMap<HandType, usize>,
+    pub hands_dealt: usize,
+    pub mean_winnings: f64,
+    pub head_to_head: Vec<HeadToHead>,
+}
+
+impl Simulation {
+    /// Deals `count` hands from a fresh deck.
+    fn deal(&self, deck: &[char], count: usize, rng: &mut StdRng) -> Result<Vec<String>, String> {
+        if self.deck.replacement {
+            return Ok((0..count)
+                .map(|_| (0..self.hand_size).map(|_| deck[rng.gen_range(0..deck.len())]).collect())
+                .collect());
+        }
+
+        let needed = count * self.hand_size;
+        if needed > deck.len() {
+            return Err(format!("{count} hands of {} need {needed} cards, the deck has {}",
+                self.hand_size, deck.len()));
+        }
+        let mut shuffled = deck.to_vec();
+        shuffled.shuffle(rng);
+        Ok(shuffled.chunks(self.hand_size)
+            .take(count)
+            .map(|c| c.iter().collect())
+            .collect())
+    }
+
+    /// The deck left once `hand` is held, when cards aren't put back.
+    fn without(&self, deck: &[char], hand: &str) -> Result<Vec<char>, String> {
+        let mut rest = deck.to_vec();
+        if self.deck.replacement {
+            return Ok(rest);
+        }
+        for card in hand.chars() {
+            let idx = rest.iter().position(|c| *c == card)
+                .ok_or_else(|| format!("{hand}: holds more '{card}' cards than the deck has"))?;
+            rest.remove(idx);
+        }
+        Ok(rest)
+    }
+
+    pub fn run(&self, rng: &mut StdRng) -> Result<Results, String> {
+        let deck = self.deck.cards(&self.ruleset);
+        if deck.is_empty() {
+            return Err(String::from("the deck has no cards"));
+        }
+        if self.hand_size == 0 {
+            return Err(String::from("hands need at least one card"));
+        }
+        // hands of different sizes can't be compared
+        if let Some(c) = self.challengers.iter().find(|c| c.chars().count() != self.hand_size) {
+            return Err(format!("{c}: {} cards, the hands dealt have {}", c.chars().count(), self.hand_size));
+        }
+
+        let mut types = BTreeMap::new();
+        let mut hands_dealt = 0;
+        let mut total_winnings = 0u64;
+        let mut head_to_head = vec![HeadToHead::default(); self.challengers.len()];
+
+        let challengers = self.challengers.iter()
+            .map(|c| Hand::from_cards(c, 0, &self.ruleset).map_err(|e| format!("{c}: {e}")))
+            .collect::<Result<Vec<Hand>, String>>()?;
+        let opponent_decks = self.challengers.iter()
+            .map(|c| self.without(&deck, c))
+            .collect::<Result<Vec<Vec<char>>, String>>()?;
+
+        for _ in 0..self.tournaments {
+            let players = self.deal(&deck, self.bids.len(), rng)?;
+            // each challenger faces its own opponent, dealt after the
+            // tournament's hands so adding challengers doesn't change the
+            // tournaments
+            let mut opponents = vec![];
+            for opponent_deck in opponent_decks.iter() {
+                opponents.extend(self.deal(opponent_deck, 1, rng)?);
+            }
+
+            let mut hands = vec![];
+            for (cards, bid) in players.iter().zip(self.bids.iter()) {
+                let hand = Hand::from_cards(cards, *bid, &self.ruleset).expect("dealt from the ruleset");
+                *types.entry(hand.typ.clone()).or_insert(0) += 1;
+                hands_dealt += 1;
+                hands.push(hand);
+            }
+            hands.sort();
+            total_winnings += crate::total_winnings(&hands) as u64;
+
+            for ((challenger, cards), h2h) in challengers.iter().zip(opponents.iter()).zip(head_to_head.iter_mut()) {
+                let opponent = Hand::from_cards(cards, 0, &self.ruleset).expect("dealt from the ruleset");
+                match challenger.cmp(&opponent) {
+                    Ordering::Greater => h2h.wins += 1,
+                    Ordering::Equal => h2h.ties += 1,
+                    Ordering::Less => h2h.losses += 1,
+                }
+            }
+        }
+
+        Ok(Results {
+            types,
+            hands_dealt,
+            mean_winnings: total_winnings as f64 / self.tournaments.max(1) as f64,
+            head_to_head,
+        })
+    }
+}
+
+pub fn print_results(sim: &Simulation, results: &Results) {
+    println!("{} tournaments of {} hands", sim.tournaments, sim.bids.len());
+    println!();
+    println!("{:<14} {:>10} {:>8}", "type", "hands", "share");
+    for (typ, count) in results.types.iter().rev() {
+        let share = *count as f64 / results.hands_dealt.max(1) as f64;
+        println!("{:<14} {:>10} {:>7.3}%", typ.name(), count, share * 100.0);
+    }
+    println!();
+    println!("expected winnings: {:.2}", results.mean_winnings);
+
+    for (cards, h2h) in sim.challengers.iter().zip(results.head_to_head.iter()) {
+        let games = (h2h.wins + h2h.ties + h2h.losses).max(1) as f64;
+        println!("{cards} vs random hand: win {:.3}% tie {:.3}% lose {:.3}%",
+            h2h.wins as f64 / games * 100.0,
+            h2h.ties as f64 / games * 100.0,
+            h2h.losses as f64 / games * 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn simulation(replacement: bool, challengers: &[&str]) -> Simulation {
+        Simulation {
+            ruleset: Ruleset::standard(),
+            deck: Deck { copies: 4, jokers: 4, replacement },
+            hand_size: 5,
+            tournaments: 200,
+            bids: vec![765, 684, 28, 220, 483],
+            challengers: challengers.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn same_seed_same_results() {
+        for replacement in [true, false] {
+            let sim = simulation(replacement, &["AAAAK", "32T3K"]);
+            let a = sim.run(&mut StdRng::seed_from_u64(7)).unwrap();
+            let b = sim.run(&mut StdRng::seed_from_u64(7)).unwrap();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn challenger_cards_leave_the_deck() {
+        // with every ace held, no opponent can match four of them
+        let sim = simulation(false, &["AAAAK"]);
+        let results = sim.run(&mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(results.head_to_head[0].losses, 0);
+        assert_eq!(results.head_to_head[0].ties, 0);
+
+        let sim = simulation(false, &["AAAAA"]);
+        assert!(sim.run(&mut StdRng::seed_from_u64(1)).is_err());
+    }
+}
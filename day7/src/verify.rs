@@ -0,0 +1,126 @@
+use crate::rules::Ruleset;
+use crate::HandType;
+
+/// A hand the wild card heuristic in `HandType::from_str` gets wrong.
+#[derive(Debug)]
+pub struct Discrepancy {
+    pub cards: String,
+    pub heuristic: HandType,
+    pub brute_force: HandType,
+}
+
+/// Every hand of `hand_size` cards that can be dealt from `ruleset`'s labels.
+fn all_hands(ruleset: &Ruleset, hand_size: usize) -> Vec<String> {
+    let labels = &ruleset.order;
+    let mut hands = vec![];
+    let mut idx = vec![0; hand_size];
+    loop {
+        hands.push(idx.iter().map(|i| labels[*i]).collect());
+
+        // odometer: bump the last position and carry leftwards
+        let mut pos = hand_size;
+        loop {
+            if pos == 0 {
+                return hands;
+            }
+            pos -= 1;
+            idx[pos] += 1;
+            if idx[pos] < labels.len() {
+                break;
+            }
+            idx[pos] = 0;
+        }
+    }
+}
+
+/// Type of a hand with no wild cards, from how many of each label it has.
+fn plain_type(label_counts: &[u32]) -> HandType {
+    let mut counts: Vec<u32> = label_counts.iter().copied().filter(|c| *c > 0).collect();
+    counts.sort_by(|a, b| b.cmp(a));
+    HandType { counts }
+}
+
+/// Best type reachable by replacing each wild card with any label at all.
+///
+/// The type only depends on how many of each label end up in the hand, so
+/// rather than every ordering of replacements we try every multiset of
+/// them, which covers the same hands far faster.
+pub fn brute_force_type(cards: &str, ruleset: &Ruleset) -> HandType {
+    let labels = &ruleset.order;
+    let mut fixed = vec![0; labels.len()];
+    let mut wild_count = 0;
+    for c in cards.chars() {
+        if ruleset.is_wild(c) {
+            wild_count += 1;
+        } else {
+            fixed[labels.iter().position(|l| *l == c).expect("dealt from the ruleset")] += 1;
+        }
+    }
+
+    let mut best: Option<HandType> = None;
+    // non-decreasing label indices enumerate each multiset exactly once
+    let mut idx = vec![0; wild_count];
+    loop {
+        let mut label_counts = fixed.clone();
+        for i in idx.iter() {
+            label_counts[*i] += 1;
+        }
+        let typ = plain_type(&label_counts);
+        if best.as_ref().is_none_or(|b| typ > *b) {
+            best = Some(typ);
+        }
+
+        let mut pos = wild_count;
+        loop {
+            if pos == 0 {
+                return best.unwrap();
+            }
+            pos -= 1;
+            if idx[pos] + 1 < labels.len() {
+                idx[pos] += 1;
+                let v = idx[pos];
+                for later in idx[pos..].iter_mut() {
+                    *later = v;
+                }
+                break;
+            }
+        }
+    }
+}
+
+/// Checks the wild card heuristic against `brute_force_type` for every
+/// possible hand.
+pub fn verify(ruleset: &Ruleset, hand_size: usize) -> (usize, Vec<Discrepancy>) {
+    let hands = all_hands(ruleset, hand_size);
+    let mut bad = vec![];
+    for cards in hands.iter() {
+        let heuristic = HandType::from_str(cards, ruleset).expect("dealt from the ruleset");
+        let brute_force = brute_force_type(cards, ruleset);
+        if heuristic != brute_force {
+            bad.push(Discrepancy {
+                cards: cards.clone(),
+                heuristic,
+                brute_force,
+            });
+        }
+    }
+    (hands.len(), bad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jokers_heuristic_is_optimal() {
+        let (checked, bad) = verify(&Ruleset::jokers(), 5);
+        assert_eq!(checked, 13usize.pow(5));
+        assert!(bad.is_empty(), "{} discrepancies, first: {:?}", bad.len(), bad.first());
+    }
+
+    #[test]
+    fn standard_has_nothing_to_substitute() {
+        let (_, bad) = verify(&Ruleset::standard(), 5);
+        assert!(bad.is_empty(), "{} discrepancies, first: {:?}", bad.len(), bad.first());
+    }
+}
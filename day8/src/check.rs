@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cycle::Cycle;
+use crate::graph::Graph;
+use crate::{Direction, Node};
+
+/// Something about the network that makes walking it meaningless.
+#[derive(Debug)]
+pub enum Problem {
+    Dangling { line: usize, node: String, target: String },
+    Duplicate { name: String, first_line: usize, line: usize },
+    MissingStart { name: String },
+    NoInstructions,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Dangling { line, node, target } =>
+                write!(f, "line {line}: {node} points at {target}, which isn't defined"),
+            Problem::Duplicate { name, first_line, line } =>
+                write!(f, "line {line}: {name} is already defined on line {first_line}"),
+            Problem::MissingStart { name } =>
+                write!(f, "start node {name} isn't defined"),
+            Problem::NoInstructions =>
+                write!(f, "line 1: no L/R instructions"),
+        }
+    }
+}
+
+/// A missing L/R line, and dangling references and duplicate definitions
+/// in `nodes`, which are (line number, node) pairs.
+pub fn structural_problems(path: &[Direction], nodes: &[(usize, Node)]) -> Vec<Problem> {
+    let mut problems = vec![];
+    if path.is_empty() {
+        problems.push(Problem::NoInstructions);
+    }
+    let mut defined: HashMap<&str, usize> = HashMap::new();
+    for (line, n) in nodes {
+        match defined.get(n.name.as_str()) {
+            Some(first_line) => problems.push(Problem::Duplicate {
+                name: n.name.clone(),
+                first_line: *first_line,
+                line: *line,
+            }),
+            None => { defined.insert(&n.name, *line); },
+        }
+    }
+    for (line, n) in nodes {
+        for (idx, target) in [&n.left, &n.right].into_iter().enumerate() {
+            // (XXX, XXX) only needs reporting once
+            let repeat = idx == 1 && n.left == n.right;
+            if !repeat && !defined.contains_key(target.as_str()) {
+                problems.push(Problem::Dangling {
+                    line: *line,
+                    node: n.name.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+    }
+    problems
+}
+
+/// Nodes reachable from `start` following either exit, ignoring the
+/// instructions.
+fn reachable(graph: &Graph, start: u32) -> Vec<bool> {
+    let mut seen = vec![false; graph.len()];
+    let mut eval = vec![start];
+    while let Some(n) = eval.pop() {
+        if seen[n as usize] {
+            continue;
+        }
+        seen[n as usize] = true;
+        eval.extend(graph.next[n as usize]);
+    }
+    seen
+}
+
+/// Prints everything wrong with the network. Returns false if it's too
+/// broken to walk at all.
+pub fn check(path: &[Direction], nodes: &[(usize, Node)]) -> bool {
+    println!("{} nodes, {} instructions", nodes.len(), path.len());
+
+    let problems = structural_problems(path, nodes);
+    for p in problems.iter() {
+        println!("error: {p}");
+    }
+    if !problems.is_empty() {
+        return false;
+    }
+
+    let node_map = Node::to_map(nodes.to_vec());
+    let graph = Graph::compile(&node_map, path);
+    let ends: Vec<bool> = graph.names.iter().map(|n| node_map[n].stop_node()).collect();
+
+    // part 1 always starts here
+    if graph.id("AAA").is_none() {
+        println!("warning: {}", Problem::MissingStart { name: String::from("AAA") });
+    }
+
+    let mut starts: Vec<&String> = graph.names.iter().filter(|n| node_map[*n].start_node()).collect();
+    if starts.is_empty() {
+        println!("warning: no start nodes");
+    }
+    starts.sort();
+
+    for name in starts {
+        let start = graph.id(name).unwrap();
+        let seen = reachable(&graph, start);
+        let unreachable: Vec<&str> = graph.names.iter()
+            .enumerate()
+            .filter(|(id, _)| ends[*id] && !seen[*id])
+            .map(|(_, n)| n.as_str())
+            .collect();
+        if !unreachable.is_empty() {
+            println!("{name}: can never reach {}", unreachable.join(", "));
+        }
+
+        let c = Cycle::find(&graph, &ends, start);
+        match c.prefix_hits.iter().chain(c.loop_hits.iter()).next() {
+            Some(first) => println!("{name}: first end at step {first}, loops every {} steps after step {}",
+                c.length, c.prefix),
+            None => println!("{name}: never lands on an end node, loops every {} steps after step {}",
+                c.length, c.prefix),
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn no_instructions() {
+        for input in ["\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)", "AAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)", ""] {
+            let (path, nodes) = parse_input(input);
+            let problems = structural_problems(&path, &nodes);
+            assert!(matches!(problems.as_slice(), [Problem::NoInstructions]), "{input:?}: {problems:?}");
+        }
+    }
+}
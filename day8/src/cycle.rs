@@ -0,0 +1,272 @@
+use std::collections::BTreeSet;
+
+use crate::graph::Graph;
+
+/// Where one walker ends up looping.
+///
+/// A walker's state is its node plus its position in the L/R path, so after
+/// at most (nodes * path length) steps some state repeats and from then on
+/// the walk is periodic.
+#[derive(Debug)]
+pub struct Cycle {
+    /// steps before the walker first enters its loop
+    pub prefix: u64,
+    /// steps around the loop
+    pub length: u64,
+    /// end nodes hit before the loop, as step counts
+    pub prefix_hits: Vec<u64>,
+    /// end nodes hit on the loop, as step counts within [prefix, prefix + length)
+    pub loop_hits: Vec<u64>,
+}
+
+impl Cycle {
+    pub fn find(graph: &Graph, ends: &[bool], start: u32) -> Cycle {
+        // (node, path index) -> step it was first seen at
+        let path_len = graph.path.len();
+        let mut seen = vec![u64::MAX; graph.len() * path_len];
+        let mut hits = vec![];
+
+        let mut cur = start;
+        let mut steps: u64 = 0;
+        loop {
+            let path_idx = (steps % path_len as u64) as usize;
+            let state = cur as usize * path_len + path_idx;
+            if seen[state] != u64::MAX {
+                let prefix = seen[state];
+                let (prefix_hits, loop_hits) = hits.iter().partition(|h| **h < prefix);
+                return Cycle {
+                    prefix,
+                    length: steps - prefix,
+                    prefix_hits,
+                    loop_hits,
+                };
+            }
+            seen[state] = steps;
+            if ends[cur as usize] {
+                hits.push(steps);
+            }
+
+            cur = graph.step(cur, path_idx);
+            steps += 1;
+        }
+    }
+}
+
+fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = ext_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Solves x = a1 (mod m1), x = a2 (mod m2) for moduli that needn't be
+/// coprime, returning (x, lcm(m1, m2)) or None if there's no solution.
+fn crt(a1: i128, m1: i128, a2: i128, m2: i128) -> Option<(i128, i128)> {
+    let (g, p, _) = ext_gcd(m1, m2);
+    if (a2 - a1) % g != 0 {
+        return None;
+    }
+    let lcm = m1 / g * m2;
+    let k = ((a2 - a1) / g % (m2 / g)) * (p % (m2 / g)) % (m2 / g);
+    let x = (a1 + m1 * k).rem_euclid(lcm);
+    Some((x, lcm))
+}
+
+// past this many residues the combinations aren't worth tracking, so fall
+// back to stepping through one walker's hits
+const MAX_RESIDUES: usize = 10_000;
+
+impl Cycle {
+    /// Whether the walker is on an end node after `steps`, for any step at
+    /// or after the loop starts.
+    fn hits_on_loop(&self, steps: u64) -> bool {
+        let offset = (steps - self.prefix) % self.length;
+        self.loop_hits.iter().any(|h| h - self.prefix == offset)
+    }
+}
+
+/// Smallest x + k * m that's at or after `from`.
+fn at_or_after(x: i128, m: i128, from: i128) -> i128 {
+    if x >= from {
+        x
+    } else {
+        x + (from - x + m - 1) / m * m
+    }
+}
+
+/// First step count at or after `from` where every cycle is on an end node,
+/// considering only hits on the loops, so `from` should be at least every
+/// cycle's prefix.
+pub fn first_common_loop_hit(cycles: &[Cycle], from: u64) -> Option<u64> {
+    // the steps where every walker so far is on an end, as residues modulo
+    // the lcm of their loop lengths, merged in one walker at a time
+    let mut residues: BTreeSet<i128> = BTreeSet::from([0]);
+    let mut modulus: i128 = 1;
+    for c in cycles {
+        let length = c.length as i128;
+        let mut next = BTreeSet::new();
+        let mut next_modulus = modulus;
+        for x in residues.iter() {
+            for hit in c.loop_hits.iter() {
+                let residue = (*hit % c.length) as i128;
+                if let Some((solved, lcm)) = crt(*x, modulus, residue, length) {
+                    next.insert(solved);
+                    next_modulus = lcm;
+                }
+            }
+        }
+        if next.is_empty() {
+            return None;
+        }
+        if next.len() > MAX_RESIDUES || next_modulus > i64::MAX as i128 {
+            return step_through_hits(cycles, from);
+        }
+        residues = next;
+        modulus = next_modulus;
+    }
+
+    residues.iter()
+        .map(|x| at_or_after(*x, modulus, from as i128))
+        .min()
+        .map(|t| t as u64)
+}
+
+/// The slow way: try every loop hit of the walker with the longest loop in
+/// turn and check the others are on an end too. Gives up once it's been
+/// round the lcm of every loop length, or if that won't fit in a u64.
+fn step_through_hits(cycles: &[Cycle], from: u64) -> Option<u64> {
+    let lead = cycles.iter().max_by_key(|c| c.length)?;
+    let mut lcm: u64 = 1;
+    for c in cycles {
+        let (g, _, _) = ext_gcd(lcm as i128, c.length as i128);
+        lcm = lcm.saturating_mul(c.length / g as u64);
+    }
+    let limit = from.saturating_add(lcm);
+
+    let mut hits: Vec<u64> = lead.loop_hits.iter()
+        .map(|h| at_or_after(*h as i128, lead.length as i128, from as i128) as u64)
+        .collect();
+    hits.sort();
+    let mut round: u64 = 0;
+    loop {
+        for h in hits.iter() {
+            let steps = h.checked_add(round.checked_mul(lead.length)?)?;
+            if steps >= limit {
+                return None;
+            }
+            if cycles.iter().all(|c| c.hits_on_loop(steps)) {
+                return Some(steps);
+            }
+        }
+        round += 1;
+    }
+}
+
+/// First step count where every walker starting from `starts` is on an end
+/// node at once. `cycles` are the walkers' cycles, in the same order.
+pub fn first_common_end(graph: &Graph, ends: &[bool], starts: &[u32], cycles: &[Cycle]) -> Option<u64> {
+    // before every walker is on its loop the hits don't repeat, so just walk
+    // them all together
+    let settled = cycles.iter().map(|c| c.prefix).max().unwrap_or(0);
+    let mut cur_nodes = starts.to_vec();
+    for steps in 0..settled {
+        if cur_nodes.iter().all(|n| ends[*n as usize]) {
+            return Some(steps);
+        }
+        for cn in cur_nodes.iter_mut() {
+            *cn = graph.step(*cn, (steps % graph.path.len() as u64) as usize);
+        }
+    }
+
+    // after that each walker hits ends periodically, line the periods up
+    first_common_loop_hit(cycles, settled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, Node};
+
+    fn compile(input: &str) -> Graph {
+        let (path, nodes) = parse_input(input);
+        Graph::compile(&Node::to_map(nodes), &path)
+    }
+
+    fn solve(graph: &Graph) -> Option<u64> {
+        let ends = graph.ends(|n| n.ends_with('Z'));
+        let starts: Vec<u32> = graph.names.iter()
+            .filter(|n| n.ends_with('A'))
+            .map(|n| graph.id(n).unwrap())
+            .collect();
+        let cycles: Vec<Cycle> = starts.iter().map(|s| Cycle::find(graph, &ends, *s)).collect();
+        first_common_end(graph, &ends, &starts, &cycles)
+    }
+
+    #[test]
+    fn crt_with_shared_factors() {
+        // x = 2 (mod 4), x = 4 (mod 6) is x = 10 (mod 12)
+        assert_eq!(crt(2, 4, 4, 6), Some((10, 12)));
+        // x is even mod 4 and odd mod 6, which can't both hold
+        assert_eq!(crt(1, 4, 2, 6), None);
+        assert_eq!(crt(0, 1, 3, 5), Some((3, 5)));
+    }
+
+    #[test]
+    fn first_hit_isnt_the_loop_length() {
+        // AAA -> 11B -> 11Z -> 11C -> 11D -> 11B hits an end at step 2 and
+        // every 4 steps after, 22A at step 3 and every 3 after
+        let graph = compile("L
+
+AAA = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11D, 11D)
+11D = (11B, 11B)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+");
+        let ends = graph.ends(|n| n.ends_with('Z'));
+        let c = Cycle::find(&graph, &ends, graph.id("AAA").unwrap());
+        assert_eq!((c.prefix, c.length, c.loop_hits.clone()), (1, 4, vec![2]));
+        assert_eq!(first_common_loop_hit(&[c], 1), Some(2));
+        assert_eq!(solve(&graph), Some(6));
+    }
+
+    #[test]
+    fn answer_within_the_prefix() {
+        // both walkers are on an end at step 1, before 22A reaches its loop,
+        // and never together again
+        let graph = compile("L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11Z, 11Z)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22D, 22D)
+22D = (22C, 22C)
+");
+        assert_eq!(solve(&graph), Some(1));
+    }
+
+    #[test]
+    fn many_hits_per_loop() {
+        // six walkers on loops of 7 to 23 nodes, every other node an end,
+        // is tens of thousands of hit combinations without merging residues
+        let mut input = String::from("L\n\n");
+        for (w, length) in [7, 11, 13, 17, 19, 23].into_iter().enumerate() {
+            input.push_str(&format!("{w}A = ({w}N0, {w}N0)\n"));
+            for i in 0..length {
+                let name = |i: usize| if i.is_multiple_of(2) { format!("{w}N{i}") } else { format!("{w}N{i}Z") };
+                input.push_str(&format!("{} = ({}, {})\n", name(i), name((i + 1) % length), name((i + 1) % length)));
+            }
+        }
+        // at step 2 every walker is on node 1, an end
+        assert_eq!(solve(&compile(&input)), Some(2));
+    }
+}
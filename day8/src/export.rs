@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use crate::{Direction, Node};
+
+// edges list at most this many step numbers before trailing off
+const MAX_EDGE_STEPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Dot,
+    Mermaid,
+}
+
+impl Format {
+    pub fn from_str(input: &str) -> Format {
+        match input {
+            "dot" => Format::Dot,
+            "mermaid" => Format::Mermaid,
+            _ => panic!("--format is dot or mermaid, not '{input}'"),
+        }
+    }
+}
+
+struct Edge<'a> {
+    from: &'a str,
+    to: &'a str,
+    label: &'static str,
+    /// steps of the walk that took this edge, 1 being the first move
+    steps: Vec<u64>,
+}
+
+/// Step numbers for every edge the walk from `start` takes, keyed by
+/// (node, 0 for left or 1 for right). The walk stops once it's back in a
+/// (node, instruction) state it's been in before, or after `limit` steps.
+fn walk<'a>(
+    node_map: &'a HashMap<String, Node>,
+    path: &[Direction],
+    start: &'a str,
+    limit: Option<u64>,
+) -> HashMap<(&'a str, usize), Vec<u64>> {
+    let mut taken: HashMap<(&str, usize), Vec<u64>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut cur = &node_map[start];
+    let mut steps: u64 = 0;
+    loop {
+        let path_idx = (steps % path.len() as u64) as usize;
+        if limit.is_some_and(|l| steps >= l) || !seen.insert((cur.name.as_str(), path_idx)) {
+            return taken;
+        }
+        steps += 1;
+        let (dir, next) = match path[path_idx] {
+            Direction::Left => (0, &cur.left),
+            Direction::Right => (1, &cur.right),
+        };
+        taken.entry((cur.name.as_str(), dir)).or_default().push(steps);
+        cur = &node_map[next];
+    }
+}
+
+fn edges<'a>(
+    node_map: &'a HashMap<String, Node>,
+    names: &[&'a String],
+    taken: &HashMap<(&str, usize), Vec<u64>>,
+) -> Vec<Edge<'a>> {
+    let mut edges = vec![];
+    for name in names {
+        let n = &node_map[*name];
+        let left = taken.get(&(name.as_str(), 0)).cloned().unwrap_or_default();
+        let right = taken.get(&(name.as_str(), 1)).cloned().unwrap_or_default();
+        if n.left == n.right {
+            let mut steps = [left, right].concat();
+            steps.sort();
+            edges.push(Edge { from: name, to: &n.left, label: "L/R", steps });
+        } else {
+            edges.push(Edge { from: name, to: &n.left, label: "L", steps: left });
+            edges.push(Edge { from: name, to: &n.right, label: "R", steps: right });
+        }
+    }
+    edges
+}
+
+fn edge_label(e: &Edge) -> String {
+    if e.steps.is_empty() {
+        return String::from(e.label);
+    }
+    let mut shown: Vec<String> = e.steps.iter().take(MAX_EDGE_STEPS).map(|s| s.to_string()).collect();
+    if e.steps.len() > MAX_EDGE_STEPS {
+        shown.push(String::from("..."));
+    }
+    format!("{} {}", e.label, shown.join(","))
+}
+
+/// The network as DOT or Mermaid, start (..A) and end (..Z) nodes
+/// highlighted and, given `walk_from`, the edges that walk takes labelled
+/// with their step numbers.
+pub fn render(
+    node_map: &HashMap<String, Node>,
+    path: &[Direction],
+    format: Format,
+    walk_from: Option<&str>,
+    limit: Option<u64>,
+) -> String {
+    let mut names: Vec<&String> = node_map.keys().collect();
+    names.sort();
+
+    let taken = match walk_from {
+        Some(start) => walk(node_map, path, start, limit),
+        None => HashMap::new(),
+    };
+    let edges = edges(node_map, &names, &taken);
+
+    let mut out = String::new();
+    match format {
+        Format::Dot => {
+            writeln!(out, "digraph network {{").unwrap();
+            for name in names.iter() {
+                let n = &node_map[*name];
+                if n.start_node() {
+                    writeln!(out, "  \"{name}\" [style=filled, fillcolor=palegreen];").unwrap();
+                } else if n.stop_node() {
+                    writeln!(out, "  \"{name}\" [style=filled, fillcolor=lightcoral];").unwrap();
+                }
+            }
+            for e in edges.iter() {
+                let style = if e.steps.is_empty() { "" } else { ", color=blue, penwidth=2" };
+                writeln!(out, "  \"{}\" -> \"{}\" [label=\"{}\"{style}];", e.from, e.to, edge_label(e)).unwrap();
+            }
+            writeln!(out, "}}").unwrap();
+        },
+        Format::Mermaid => {
+            writeln!(out, "flowchart LR").unwrap();
+            for (idx, e) in edges.iter().enumerate() {
+                writeln!(out, "  {} -->|\"{}\"| {}", e.from, edge_label(e), e.to).unwrap();
+                if !e.steps.is_empty() {
+                    writeln!(out, "  linkStyle {idx} stroke:blue,stroke-width:2px").unwrap();
+                }
+            }
+            writeln!(out, "  classDef start fill:#afa").unwrap();
+            writeln!(out, "  classDef stop fill:#f99").unwrap();
+            for name in names.iter() {
+                let n = &node_map[*name];
+                if n.start_node() {
+                    writeln!(out, "  class {name} start").unwrap();
+                } else if n.stop_node() {
+                    writeln!(out, "  class {name} stop").unwrap();
+                }
+            }
+        },
+    }
+    out
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use crate::{Direction, Node};
+
+/// The network with names swapped for dense ids so a step is two array
+/// lookups rather than a string hash.
+pub struct Graph {
+    /// id -> name
+    pub names: Vec<String>,
+    ids: HashMap<String, u32>,
+    /// id -> [left, right]
+    pub next: Vec<[u32; 2]>,
+    /// the L/R instructions as indexes into `next`, 0 left and 1 right
+    pub path: Vec<u8>,
+}
+
+/// Where one full pass of the instructions takes a node.
+#[derive(Debug, Clone, Copy)]
+pub struct Pass {
+    pub to: u32,
+    /// offset into the pass of the first end node, 0 being the node the
+    /// pass starts on
+    pub first_end: Option<u32>,
+}
+
+impl Graph {
+    pub fn compile(node_map: &HashMap<String, Node>, path: &[Direction]) -> Graph {
+        // sorted so ids don't depend on HashMap order
+        let mut names: Vec<String> = node_map.keys().cloned().collect();
+        names.sort();
+        let ids: HashMap<String, u32> = names.iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id as u32))
+            .collect();
+
+        let next = names.iter()
+            .map(|name| {
+                let n = &node_map[name];
+                [ids[&n.left], ids[&n.right]]
+            })
+            .collect();
+
+        let path = path.iter()
+            .map(|d| match d {
+                Direction::Left => 0,
+                Direction::Right => 1,
+            })
+            .collect();
+
+        Graph { names, ids, next, path }
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Node after taking instruction `path_idx` (mod the path length) from
+    /// `node`.
+    pub fn step(&self, node: u32, path_idx: usize) -> u32 {
+        self.next[node as usize][self.path[path_idx % self.path.len()] as usize]
+    }
+
+    /// id -> whether `end` accepts its name
+    pub fn ends<F>(&self, end: F) -> Vec<bool>
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.names.iter().map(|n| end(n)).collect()
+    }
+
+    /// One `Pass` per node: where a full run through the instructions ends up
+    /// and whether it crosses an end node on the way.
+    pub fn pass_table(&self, ends: &[bool]) -> Vec<Pass> {
+        (0..self.len() as u32)
+            .map(|start| {
+                let mut node = start;
+                let mut first_end = None;
+                for (idx, dir) in self.path.iter().enumerate() {
+                    if first_end.is_none() && ends[node as usize] {
+                        first_end = Some(idx as u32);
+                    }
+                    node = self.next[node as usize][*dir as usize];
+                }
+                Pass { to: node, first_end }
+            })
+            .collect()
+    }
+
+    /// Steps from `start` to the first end node, skipping a whole pass at a
+    /// time when it has none. None if the walk never reaches one.
+    pub fn walk(&self, passes: &[Pass], start: u32) -> Option<u64> {
+        // every pass starts at instruction 0, so landing on the same node at
+        // the start of two passes means we're going round in circles
+        let mut seen = vec![false; self.len()];
+        let mut node = start;
+        let mut steps: u64 = 0;
+        loop {
+            let pass = passes[node as usize];
+            if let Some(offset) = pass.first_end {
+                return Some(steps + offset as u64);
+            }
+            if seen[node as usize] {
+                return None;
+            }
+            seen[node as usize] = true;
+            node = pass.to;
+            steps += self.path.len() as u64;
+        }
+    }
+}
@@ -1,7 +1,18 @@
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::str::Lines;
 
+use regex::Regex;
+
+use cycle::Cycle;
+use graph::Graph;
+
+mod check;
+mod cycle;
+mod export;
+mod graph;
+
 #[derive(Debug)]
 enum Direction{
     Left,
@@ -9,7 +20,7 @@ enum Direction{
 }
 
 impl Direction {
-    fn parse_path(input: &String) -> Vec<Direction> {
+    fn parse_path(input: &str) -> Vec<Direction> {
         let mut path = vec![];
         for c in input.as_bytes() {
             match *c {
@@ -18,11 +29,11 @@ impl Direction {
                 _ => { panic!("LR only"); },
             }
         }
-        return path;
+        path
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Node {
     name: String,
     left: String,
@@ -30,15 +41,15 @@ struct Node {
 }
 
 impl Node {
-    fn from_str(input: &String) -> Node {
-        let mut parts = input.split("=");
-        let name = parts.nth(0).unwrap().trim();
-        let rest = parts.nth(0).unwrap();
-        let x = rest.replace("(", "");
-        let y = x.replace(")", "");
-        let mut next = y.split(",");
-        let left = next.nth(0).unwrap().trim();
-        let right = next.nth(0).unwrap().trim();
+    fn from_str(input: &str) -> Node {
+        let mut parts = input.split('=');
+        let name = parts.next().unwrap().trim();
+        let rest = parts.next().unwrap();
+        let x = rest.replace('(', "");
+        let y = x.replace(')', "");
+        let mut next = y.split(',');
+        let left = next.next().unwrap().trim();
+        let right = next.next().unwrap().trim();
 
         // println!("{name} -> {left}, {right}");
 
@@ -49,100 +60,279 @@ impl Node {
         }
     }
 
-    fn parse_map(input: Lines<'_>) -> HashMap<String, Node> {
-        let mut node_map = HashMap::new();
-        for l in input {
+    /// Nodes in input order with their line numbers, `input` starting on
+    /// `first_line`.
+    fn parse_list(input: Lines<'_>, first_line: usize) -> Vec<(usize, Node)> {
+        let mut nodes = vec![];
+        for (idx, l) in input.enumerate() {
             let line = l.trim();
-            if line.len() == 0 { continue; }
+            if line.is_empty() { continue; }
             
-            let n = Node::from_str(&String::from(line));
+            nodes.push((first_line + idx, Node::from_str(line)));
+        }
+        nodes
+    }
+
+    fn to_map(nodes: Vec<(usize, Node)>) -> HashMap<String, Node> {
+        let mut node_map = HashMap::new();
+        for (_, n) in nodes {
             node_map.insert(n.name.clone(), n);
         }
-        return node_map;
+        node_map
     }
 
     fn start_node(&self) -> bool {
-        self.name.ends_with("A")
+        self.name.ends_with('A')
     }
 
     fn stop_node(&self) -> bool {
-        self.name.ends_with("Z")
+        self.name.ends_with('Z')
     }
 }
 
-fn part1(input: &String, start: &String, end: &dyn Fn(&String) -> bool) {
-    let mut lines = input.lines();
-    let path_line = lines.nth(0).unwrap();
-    let path = Direction::parse_path(&path_line.to_string());
-    // println!("{:?}", path);
-    let node_map = Node::parse_map(lines);
-    let steps = common(&node_map, &path, start, end);
-    println!("steps: {steps}");
+/// Picks out start or end nodes by name.
+enum Selector {
+    Name(String),
+    Prefix(String),
+    Suffix(String),
+    Regex(Regex),
 }
 
-fn common<F>(node_map: &HashMap<String, Node>, path: &Vec<Direction>, start: &String, end: F) -> usize
-where
-    F: Fn(&String) -> bool,
-{
-    // println!("{:?}", node_map);
-
-    let mut steps = 0;
-
-    let mut cur = node_map.get(start).unwrap();
-    while !end(&cur.name) {
-        // println!("cur: {}", &cur.name);
-        let next_dir = path.get(steps % path.len()).unwrap();
-        match next_dir {
-            Direction::Left => {
-                // println!("Left");
-                cur = &node_map[&cur.left];
-            },
-            Direction::Right => {
-                // println!("Right");
-                cur = &node_map[&cur.right];
-            }
+impl Selector {
+    /// `name:AAA`, `prefix:X`, `suffix:Z` or `regex:^1.Z$`; a bare name is
+    /// an exact match.
+    fn from_str(spec: &str) -> Result<Selector, String> {
+        let (kind, value) = spec.split_once(':').unwrap_or(("name", spec));
+        let value = value.to_string();
+        match kind {
+            "name" => Ok(Selector::Name(value)),
+            "prefix" => Ok(Selector::Prefix(value)),
+            "suffix" => Ok(Selector::Suffix(value)),
+            "regex" => Regex::new(&value)
+                .map(Selector::Regex)
+                .map_err(|e| format!("bad regex '{value}': {e}")),
+            _ => Err(format!("'{kind}' isn't name, prefix, suffix or regex")),
         }
-        steps = steps + 1;
     }
 
-    // println!("steps: {steps}");
-    return steps;
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Selector::Name(n) => name == n,
+            Selector::Prefix(p) => name.starts_with(p.as_str()),
+            Selector::Suffix(s) => name.ends_with(s.as_str()),
+            Selector::Regex(r) => r.is_match(name),
+        }
+    }
 }
 
-fn all_stop(nodes: &Vec<&Node>) -> bool {
-    nodes.iter().all(|v| v.stop_node())
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Name(n) => write!(f, "name:{n}"),
+            Selector::Prefix(p) => write!(f, "prefix:{p}"),
+            Selector::Suffix(s) => write!(f, "suffix:{s}"),
+            Selector::Regex(r) => write!(f, "regex:{}", r.as_str()),
+        }
+    }
+}
+
+/// A parsed network that's safe to walk: every target is defined and
+/// names are unique.
+struct Network {
+    path: Vec<Direction>,
+    node_map: HashMap<String, Node>,
+    graph: Graph,
+}
+
+fn exit_with(msg: &str) -> ! {
+    eprintln!("error: {msg}");
+    std::process::exit(1);
 }
 
-fn part2(input: &String) {
+fn parse_input(input: &str) -> (Vec<Direction>, Vec<(usize, Node)>) {
     let mut lines = input.lines();
-    let path_line = lines.nth(0).unwrap();
-    let path = Direction::parse_path(&path_line.to_string());
-    let node_map = Node::parse_map(lines);
+    let path_line = lines.next().unwrap_or("");
+    // no L/R line at all, the nodes start straight away
+    if path_line.contains('=') {
+        return (vec![], Node::parse_list(input.lines(), 1));
+    }
+    let path = Direction::parse_path(path_line.trim());
+    // println!("{:?}", path);
+    (path, Node::parse_list(lines, 2))
+}
 
-    let start_nodes: Vec<&String> = node_map.values()
-        .filter(|v| v.start_node())
-        .map(|v| &v.name)
+/// Parses and validates `input`, exiting with the problems if it can't be
+/// walked.
+fn load(input: &str) -> Network {
+    let (path, nodes) = parse_input(input);
+    let problems = check::structural_problems(&path, &nodes);
+    if !problems.is_empty() {
+        for p in problems.iter() {
+            eprintln!("error: {p}");
+        }
+        std::process::exit(1);
+    }
+    let node_map = Node::to_map(nodes);
+    let graph = Graph::compile(&node_map, &path);
+    Network { path, node_map, graph }
+}
+
+/// Names of every node `start` picks out, sorted, exiting if there are
+/// none.
+fn start_names<'a>(network: &'a Network, start: &Selector) -> Vec<&'a str> {
+    let mut names: Vec<&str> = network.graph.names.iter()
+        .map(|n| n.as_str())
+        .filter(|n| start.matches(n))
         .collect();
+    if names.is_empty() {
+        match start {
+            Selector::Name(name) =>
+                exit_with(&check::Problem::MissingStart { name: name.clone() }.to_string()),
+            _ => exit_with(&format!("no node matches {start}")),
+        }
+    }
+    names.sort();
+    names
+}
 
-    let mut cur_nodes: Vec<&Node> = vec![];
+/// Walks each start on its own until it reaches an end.
+fn part1(network: &Network, start: &Selector, end: &Selector) {
+    let starts = start_names(network, start);
+    for name in starts.iter() {
+        match common(&network.graph, name, |n| end.matches(n)) {
+            Some(steps) if starts.len() == 1 => println!("steps: {steps}"),
+            Some(steps) => println!("{name} -> steps: {steps}"),
+            None => exit_with(&format!("{name} never reaches an end node")),
+        }
+    }
+}
+
+fn common<F>(graph: &Graph, start: &str, end: F) -> Option<u64>
+where
+    F: Fn(&str) -> bool,
+{
+    let ends = graph.ends(end);
+    let passes = graph.pass_table(&ends);
+    graph.walk(&passes, graph.id(start).unwrap())
+}
+
+/// Walks every start at once until they're all on an end together.
+fn part2(network: &Network, start: &Selector, end: &Selector) {
+    let graph = &network.graph;
+    let start_nodes = start_names(network, start);
+
+    let ends = graph.ends(|n| end.matches(n));
+    let mut cycles = vec![];
     for sn in start_nodes.iter() {
-        cur_nodes.push(node_map.get(*sn).unwrap());
+        let c = Cycle::find(graph, &ends, graph.id(sn).unwrap());
+        println!("{} -> prefix {} loop {} hits {:?} {:?}",
+            sn, c.prefix, c.length, c.prefix_hits, c.loop_hits);
+        cycles.push(c);
     }
 
-    for cn in cur_nodes {
-        let cn_steps = common(&node_map, &path, &cn.name, |x| x.ends_with("Z"));
-        println!("{} -> {}", &cn.name, cn_steps);
+    let starts: Vec<u32> = start_nodes.iter().map(|n| graph.id(n).unwrap()).collect();
+    match cycle::first_common_end(graph, &ends, &starts, &cycles) {
+        Some(steps) => println!("steps: {steps}"),
+        None => exit_with("the walkers are never all on an end node together"),
     }
+}
 
-    println!("find LCM for the above");
+/// Removes `flag` and the value following it from `args`.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let idx = args.iter().position(|a| a == flag)?;
+    args.remove(idx);
+    if idx >= args.len() {
+        panic!("{flag} needs a value");
+    }
+    Some(args.remove(idx))
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let mode = match args.first().map(|a| a.as_str()) {
+        Some("check") | Some("graph") => Some(args.remove(0)),
+        _ => None,
+    };
+
+    // day8 graph [input] [--format dot|mermaid] [--walk START] [--steps N]
+    let format = take_flag(&mut args, "--format").map_or(export::Format::Dot, |f| export::Format::from_str(&f));
+    let walk_from = take_flag(&mut args, "--walk");
+    let limit = take_flag(&mut args, "--steps").map(|v| v.parse().expect("--steps number"));
+
+    // day8 [input] [--start SEL] [--end SEL] [--walkers single|all]
+    // where SEL is name:AAA, prefix:X, suffix:Z or regex:...
+    let start = take_flag(&mut args, "--start").map(|s| Selector::from_str(&s).unwrap_or_else(|e| exit_with(&e)));
+    let end = take_flag(&mut args, "--end").map(|s| Selector::from_str(&s).unwrap_or_else(|e| exit_with(&e)));
+    let walkers = take_flag(&mut args, "--walkers");
+
+    // day8 [check] [input]
     let default_path = String::from("test.txt");
-    let fp = args.get(1).unwrap_or(&default_path);
+    let fp = args.first().unwrap_or(&default_path);
 
     let input = std::fs::read_to_string(fp).expect("input");
-    part1(&input, &String::from("AAA"), &|x| x == "ZZZ");
-    part2(&input);
+    if mode.as_deref() == Some("graph") {
+        let network = load(&input);
+        if let Some(start) = walk_from.as_deref() {
+            if network.graph.id(start).is_none() {
+                exit_with(&check::Problem::MissingStart { name: start.to_string() }.to_string());
+            }
+        }
+        print!("{}", export::render(&network.node_map, &network.path, format, walk_from.as_deref(), limit));
+        return;
+    }
+    if mode.as_deref() == Some("check") {
+        let (path, nodes) = parse_input(&input);
+        if !check::check(&path, &nodes) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let network = load(&input);
+    if start.is_none() && end.is_none() && walkers.is_none() {
+        // part 2 fixtures have no AAA, which shouldn't stop part 2 running
+        if network.graph.id("AAA").is_some() {
+            part1(&network, &Selector::Name(String::from("AAA")), &Selector::Name(String::from("ZZZ")));
+        } else {
+            eprintln!("warning: {}, skipping part 1", check::Problem::MissingStart { name: String::from("AAA") });
+        }
+        part2(&network, &Selector::Suffix(String::from("A")), &Selector::Suffix(String::from("Z")));
+        return;
+    }
+
+    let start = start.unwrap_or_else(|| Selector::Name(String::from("AAA")));
+    let end = end.unwrap_or_else(|| Selector::Name(String::from("ZZZ")));
+    match walkers.as_deref() {
+        None | Some("single") => part1(&network, &start, &end),
+        Some("all") => part2(&network, &start, &end),
+        Some(other) => exit_with(&format!("--walkers is single or all, not '{other}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selectors() {
+        let name = Selector::from_str("name:AAA").unwrap();
+        assert!(name.matches("AAA") && !name.matches("AAAB"));
+        assert!(Selector::from_str("ZZZ").unwrap().matches("ZZZ"));
+
+        let prefix = Selector::from_str("prefix:1").unwrap();
+        assert!(prefix.matches("11A") && !prefix.matches("21A"));
+
+        let suffix = Selector::from_str("suffix:Z").unwrap();
+        assert!(suffix.matches("11Z") && !suffix.matches("Z1A"));
+
+        let regex = Selector::from_str("regex:^1.Z$").unwrap();
+        assert!(regex.matches("1BZ") && !regex.matches("11BZ"));
+        assert_eq!(regex.to_string(), "regex:^1.Z$");
+    }
+
+    #[test]
+    fn bad_selectors() {
+        assert_eq!(Selector::from_str("middle:B").err().unwrap(), "'middle' isn't name, prefix, suffix or regex");
+        assert!(Selector::from_str("regex:(").err().unwrap().starts_with("bad regex '('"));
+    }
 }
@@ -0,0 +1,108 @@
+/// A least-squares polynomial through a history that the difference pyramid
+/// can't explain exactly.
+#[derive(Debug)]
+pub struct Fit {
+    /// coefficients in the scaled variable t = (k - center) / scale, lowest
+    /// power first
+    coefficients: Vec<f64>,
+    center: f64,
+    scale: f64,
+    /// root mean square distance between the fit and the readings
+    pub residual: f64,
+}
+
+impl Fit {
+    /// Fits a polynomial of `degree` (capped at one less than the number of
+    /// readings) to readings taken at 0, 1, 2, ...
+    pub fn least_squares(history: &[f64], degree: usize) -> Fit {
+        let n = history.len();
+        let cols = (degree + 1).min(n).max(1);
+
+        // centre and scale the indexes so the powers stay near 1, otherwise
+        // the columns are too close to parallel to solve accurately
+        let center = (n as f64 - 1.0) / 2.0;
+        let scale = center.max(1.0);
+
+        // columns of the Vandermonde matrix, orthonormalised as we go
+        // (modified Gram-Schmidt), with r the upper triangular factor stored
+        // a column at a time
+        let mut q: Vec<Vec<f64>> = vec![];
+        let mut r: Vec<Vec<f64>> = vec![];
+        for j in 0..cols {
+            let mut r_col = vec![0.0; cols];
+            let mut v: Vec<f64> = (0..n).map(|i| ((i as f64 - center) / scale).powi(j as i32)).collect();
+            for (k, qk) in q.iter().enumerate() {
+                let dot: f64 = qk.iter().zip(v.iter()).map(|(a, b)| a * b).sum();
+                r_col[k] = dot;
+                for (vi, qi) in v.iter_mut().zip(qk.iter()) {
+                    *vi -= dot * qi;
+                }
+            }
+            let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            r_col[j] = norm;
+            r.push(r_col);
+            q.push(v.iter().map(|x| if norm == 0.0 { 0.0 } else { x / norm }).collect());
+        }
+
+        // solve r c = q^T y by back substitution
+        let qty: Vec<f64> = q.iter()
+            .map(|qk| qk.iter().zip(history.iter()).map(|(a, y)| a * y).sum())
+            .collect();
+        let mut coefficients = vec![0.0; cols];
+        for j in (0..cols).rev() {
+            let mut acc = qty[j];
+            for k in (j + 1)..cols {
+                acc -= r[k][j] * coefficients[k];
+            }
+            coefficients[j] = if r[j][j] == 0.0 { 0.0 } else { acc / r[j][j] };
+        }
+
+        let mut fit = Fit { coefficients, center, scale, residual: 0.0 };
+        let sum_sq: f64 = history.iter()
+            .enumerate()
+            .map(|(i, y)| (fit.value_at(i as f64) - y).powi(2))
+            .sum();
+        fit.residual = (sum_sq / n.max(1) as f64).sqrt();
+        fit
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    pub fn value_at(&self, k: f64) -> f64 {
+        let t = (k - self.center) / self.scale;
+        // Horner's rule
+        self.coefficients.iter().rev().fold(0.0, |acc, c| acc * t + c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_on_a_polynomial() {
+        // 2k^2 - 3k + 1
+        let history: Vec<f64> = (0..8).map(|k| (2 * k * k - 3 * k + 1) as f64).collect();
+        let f = Fit::least_squares(&history, 2);
+        assert_eq!(f.degree(), 2);
+        assert!(f.residual < 1e-9, "{}", f.residual);
+        assert!((f.value_at(8.0) - 105.0).abs() < 1e-6);
+        assert!((f.value_at(-1.0) - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn residual_off_a_polynomial() {
+        let history = [1.0, 2.0, 4.0, 8.0, 16.0];
+        let f = Fit::least_squares(&history, 1);
+        // best line is y = 3.6k - 1, squared errors summing to 19.2
+        assert!((f.value_at(4.0) - 13.4).abs() < 1e-9);
+        assert!((f.residual - (19.2f64 / 5.0).sqrt()).abs() < 1e-9, "{}", f.residual);
+
+        // the degree is capped by the number of readings
+        let f = Fit::least_squares(&history, 9);
+        assert_eq!(f.degree(), 4);
+        assert!(f.residual < 1e-9);
+    }
+}
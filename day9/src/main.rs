@@ -1,6 +1,14 @@
-use std::collections::HashMap;
 use std::env;
 
+use fit::Fit;
+use newton::ExtrapolationError;
+use numeric::Arithmetic;
+
+mod fit;
+mod newton;
+mod numeric;
+mod show;
+
 /*
 To do this, start by making a new sequence from the difference at each step of
 your history. If that sequence is not all zeroes, repeat this process, using
@@ -11,88 +19,303 @@ the original history should be.
 
 fn num_str_to_vec(input: &str) -> Vec<i64> {
     let mut r = Vec::new();
-    for e in input.split(" ") {
-        if e.len() == 0 {
+    for e in input.split(' ') {
+        if e.is_empty() {
             continue;
         }
         r.push(e.trim().parse().expect("a number"));
     }
-    return r;
+    r
 }
 
-fn perform_diffs(input: &Vec<i64>) -> Vec<i64> {
+fn perform_diffs(input: &[i64]) -> Vec<i64> {
     let mut diffs = vec![];
-    for idx in 0..(input.len() - 1) {
-        diffs.push(input[idx+1] - input[idx]);
+    for pair in input.windows(2) {
+        diffs.push(pair[1] - pair[0]);
     }
-    return diffs;
+    diffs
 }
 
-fn perform_diffs_2(input: &Vec<i64>) -> Vec<i64> {
-    let mut diffs = vec![];
-    for idx_offset in 1..(input.len()) {
-        let idx = input.len() - idx_offset;
-        diffs.push(input[idx] - input[idx-1]);
-    }
-    return diffs;
-}
-
-fn final_step(input: &Vec<i64>) -> bool {
+fn final_step(input: &[i64]) -> bool {
     input.iter().all(|x| *x == 0)
 }
 
-fn recurse(cur: &mut Vec<i64>) {
-    if final_step(cur) {
-        cur.push(0);
+/// The pyramid built row by row, in the backend's numbers. None if some
+/// step doesn't fit.
+fn recurse<A: Arithmetic>(a: &A, cur: &mut Vec<A::Value>) -> Option<()> {
+    if cur.iter().all(|x| a.is_zero(x)) {
+        cur.push(a.lift(0));
     } else {
-        let mut next = perform_diffs(cur);
-        recurse(&mut next);
+        let mut next = differences(a, cur)?;
+        recurse(a, &mut next)?;
         let last_cur = cur.last().unwrap();
         let last_next = next.last().unwrap();
-        cur.push(*last_cur + *last_next);
+        cur.push(a.add(last_cur, last_next)?);
     }
+    Some(())
 }
 
-fn recurse_2(cur: &mut Vec<i64>) {
-    if final_step(cur) {
-        cur.push(0);
+fn recurse_2<A: Arithmetic>(a: &A, cur: &mut Vec<A::Value>) -> Option<()> {
+    if cur.iter().all(|x| a.is_zero(x)) {
+        cur.push(a.lift(0));
     } else {
-        let mut next = perform_diffs(cur);
-        recurse_2(&mut next);
+        let mut next = differences(a, cur)?;
+        recurse_2(a, &mut next)?;
         let first_cur = cur.first().unwrap();
-        let first_next = next.first().unwrap(); 
-        cur.insert(0, first_cur - first_next);
+        let first_next = next.first().unwrap();
+        cur.insert(0, a.sub(first_cur, first_next)?);
+    }
+    Some(())
+}
+
+fn differences<A: Arithmetic>(a: &A, input: &[A::Value]) -> Option<Vec<A::Value>> {
+    input.windows(2).map(|pair| a.sub(&pair[1], &pair[0])).collect()
+}
+
+/// A line's readings in the backend's numbers, so histories too big for
+/// i64 still load under the others. A number the backend can't hold is an
+/// error, text that isn't a number at all is a bug in the input.
+fn parse_readings<A: Arithmetic>(a: &A, line: usize, input: &str) -> Result<Vec<A::Value>, ExtrapolationError> {
+    input.split(' ')
+        .filter(|e| !e.is_empty())
+        .map(|e| match a.parse(e.trim()) {
+            Some(v) => Ok(v),
+            None => {
+                numeric::Rational.parse(e.trim()).expect("a number");
+                Err(ExtrapolationError::Arithmetic { line, reason: format!("reading {} {}", e.trim(), a.limitation()) })
+            },
+        })
+        .collect()
+}
+
+fn exit_with(e: ExtrapolationError) -> ! {
+    eprintln!("error: {e}");
+    std::process::exit(1);
+}
+
+/// Extrapolates one history to index `k`. Histories that aren't
+/// polynomial are fatal unless `fit` gives a degree for a least-squares
+/// fallback.
+fn extrapolate_line<A: Arithmetic>(
+    a: &A,
+    line: usize,
+    readings: &[A::Value],
+    k: &A::Value,
+    fit: Option<usize>,
+) -> A::Value {
+    let e = match newton::extrapolate(a, line, readings, k) {
+        Ok(v) => return v,
+        Err(e) => e,
+    };
+    let degree = match (&e, fit) {
+        (ExtrapolationError::NotPolynomial { .. }, Some(degree)) => degree,
+        _ => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        },
+    };
+
+    let approx: Option<Vec<f64>> = readings.iter().map(|r| a.to_f64(r)).collect();
+    let (Some(history), Some(k)) = (approx, a.to_f64(k)) else {
+        eprintln!("error: {e}, and these numbers can't be fitted");
+        std::process::exit(1);
+    };
+    let f = Fit::least_squares(&history, degree);
+    let v = f.value_at(k);
+    eprintln!("{e}; degree {} least-squares fit, residual {:.4}, gives {v:.4}", f.degree(), f.residual);
+    a.parse(&format!("{v:.0}")).unwrap_or_else(|| {
+        let reason = format!("fitted value {v:.0} {}", a.limitation());
+        eprintln!("error: {}", ExtrapolationError::Arithmetic { line, reason });
+        std::process::exit(1);
+    })
+}
+
+/// Extrapolates one history by building its pyramid, taking the new
+/// reading from the end or the start. Histories that aren't polynomial are
+/// fatal, as they are for Newton's formula.
+fn recurse_line<A: Arithmetic>(a: &A, line: usize, input: &str, backward: bool) -> A::Value {
+    let arithmetic = || ExtrapolationError::Arithmetic { line, reason: a.limitation() };
+
+    let mut readings = parse_readings(a, line, input).unwrap_or_else(|e| exit_with(e));
+    if readings.is_empty() {
+        exit_with(ExtrapolationError::Empty { line });
+    }
+    match reaches_zero_row(a, &readings) {
+        Some(true) => {},
+        Some(false) => exit_with(ExtrapolationError::NotPolynomial { line, degree: readings.len() - 1 }),
+        None => exit_with(arithmetic()),
+    }
+    let built = if backward { recurse_2(a, &mut readings) } else { recurse(a, &mut readings) };
+    if built.is_none() {
+        exit_with(arithmetic());
     }
+    if backward { readings.swap_remove(0) } else { readings.pop().unwrap() }
 }
 
-fn part1(input: &String) {
-    let mut acc = 0;
-    for l in input.lines() {
-        let mut readings = num_str_to_vec(l);
-        recurse(&mut readings);
-        acc += readings.last().unwrap();
+/// Whether some row of the pyramid is all zero before the rows run out,
+/// None if a difference doesn't fit.
+fn reaches_zero_row<A: Arithmetic>(a: &A, readings: &[A::Value]) -> Option<bool> {
+    let mut row = readings.to_vec();
+    while !row.is_empty() {
+        if row.iter().all(|x| a.is_zero(x)) {
+            return Some(true);
+        }
+        row = differences(a, &row)?;
+    }
+    Some(false)
+}
+
+fn part1<A: Arithmetic>(a: &A, input: &str, recursive: bool, fit: Option<usize>) {
+    let mut acc = a.lift(0);
+    for (idx, l) in input.lines().enumerate() {
+        let v = if recursive {
+            recurse_line(a, idx + 1, l, false)
+        } else {
+            let readings = parse_readings(a, idx + 1, l).unwrap_or_else(|e| exit_with(e));
+            let k = a.lift(readings.len() as i64);
+            extrapolate_line(a, idx + 1, &readings, &k, fit)
+        };
+        acc = add_or_exit(a, &acc, &v);
     }
 
     println!("acc: {acc}");
 }
 
-fn part2(input: &String) {
-    let mut acc = 0;
-    for l in input.lines() {
-        let mut readings = num_str_to_vec(l);
-        recurse_2(&mut readings);
-        acc += readings.first().unwrap();
+fn part2<A: Arithmetic>(a: &A, input: &str, recursive: bool, fit: Option<usize>) {
+    let mut acc = a.lift(0);
+    for (idx, l) in input.lines().enumerate() {
+        let v = if recursive {
+            recurse_line(a, idx + 1, l, true)
+        } else {
+            let readings = parse_readings(a, idx + 1, l).unwrap_or_else(|e| exit_with(e));
+            extrapolate_line(a, idx + 1, &readings, &a.lift(-1), fit)
+        };
+        acc = add_or_exit(a, &acc, &v);
     }
 
     println!("acc: {acc}");
 }
 
+/// Every history's value at index `k`, and their sum.
+fn value_at<A: Arithmetic>(a: &A, input: &str, k: &str, fit: Option<usize>) {
+    let Some(k) = a.parse(k) else {
+        eprintln!("error: {k} isn't an index these numbers can use");
+        std::process::exit(1);
+    };
+    let mut acc = a.lift(0);
+    for (idx, l) in input.lines().enumerate() {
+        let readings = parse_readings(a, idx + 1, l).unwrap_or_else(|e| exit_with(e));
+        let v = extrapolate_line(a, idx + 1, &readings, &k, fit);
+        println!("{l} -> {v}");
+        acc = add_or_exit(a, &acc, &v);
+    }
+
+    println!("acc: {acc}");
+}
+
+fn add_or_exit<A: Arithmetic>(a: &A, acc: &A::Value, v: &A::Value) -> A::Value {
+    a.add(acc, v).unwrap_or_else(|| {
+        eprintln!("error: total {}", a.limitation());
+        std::process::exit(1);
+    })
+}
+
+/// Runs whichever mode was asked for in the backend's numbers.
+fn run<A: Arithmetic>(a: &A, input: &str, at: Option<&str>, recursive: bool, fit: Option<usize>) {
+    if let Some(k) = at {
+        value_at(a, input, k, fit);
+        return;
+    }
+    part1(a, input, recursive, fit);
+    part2(a, input, recursive, fit);
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // --recursive extrapolates by building the difference pyramid instead
+    let recursive = match args.iter().position(|a| a == "--recursive") {
+        Some(idx) => {
+            args.remove(idx);
+            true
+        },
+        None => false,
+    };
+
+    // --fit <degree> falls back to a least-squares fit for histories that
+    // aren't polynomial
+    let mut fit = None;
+    if let Some(idx) = args.iter().position(|a| a == "--fit") {
+        args.remove(idx);
+        if idx >= args.len() {
+            panic!("--fit needs a degree");
+        }
+        fit = Some(args.remove(idx).parse().expect("--fit degree"));
+    }
+
+    // --numbers i64|bigint|rational|mod:<prime> picks the arithmetic,
+    // bigint by default
+    let mut numbers = String::from("bigint");
+    if let Some(idx) = args.iter().position(|a| a == "--numbers") {
+        args.remove(idx);
+        if idx >= args.len() {
+            panic!("--numbers needs a backend");
+        }
+        numbers = args.remove(idx);
+    }
+
+    // --latex or --markdown for show
+    let mut format = show::Format::Text;
+    for (flag, f) in [("--latex", show::Format::Latex), ("--markdown", show::Format::Markdown)] {
+        if let Some(idx) = args.iter().position(|a| a == flag) {
+            args.remove(idx);
+            format = f;
+        }
+    }
+
+    // day9 show <line> [input] [--latex|--markdown]
+    let mut show_line = None;
+    if args.first().is_some_and(|a| a == "show") {
+        args.remove(0);
+        if args.is_empty() {
+            panic!("usage: day9 show <line> [input] [--latex|--markdown]");
+        }
+        let line: usize = args.remove(0).parse().expect("line is a number");
+        show_line = Some(line);
+    }
+
+    // day9 at <k> [input]
+    let mut at = None;
+    if args.first().is_some_and(|a| a == "at") {
+        args.remove(0);
+        if args.is_empty() {
+            panic!("usage: day9 at <k> [input]");
+        }
+        at = Some(args.remove(0));
+    }
+
+    // day9 [input] [--recursive] [--fit <degree>] [--numbers <backend>]
     let default_path = String::from("test.txt");
-    let fp = args.get(1).unwrap_or(&default_path);
+    let fp = args.first().unwrap_or(&default_path);
 
     let input = std::fs::read_to_string(fp).expect("input");
-    part1(&input);
-    part2(&input);
-}
\ No newline at end of file
+    if let Some(line) = show_line {
+        show::show(&input, line, format);
+        return;
+    }
+    let at = at.as_deref();
+    match numbers.as_str() {
+        "i64" => run(&numeric::Checked, &input, at, recursive, fit),
+        "bigint" => run(&numeric::Big, &input, at, recursive, fit),
+        "rational" => run(&numeric::Rational, &input, at, recursive, fit),
+        m => {
+            let modular = m.strip_prefix("mod:")
+                .and_then(|p| p.parse().ok())
+                .and_then(numeric::Modular::new);
+            match modular {
+                Some(modular) => run(&modular, &input, at, recursive, fit),
+                None => panic!("--numbers is i64, bigint, rational or mod:<prime>, not '{m}'"),
+            }
+        },
+    }
+}
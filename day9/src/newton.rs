@@ -0,0 +1,200 @@
+use std::fmt;
+
+use crate::numeric::Arithmetic;
+
+/*
+A history of n readings taken at 0, 1, ..., n-1 whose difference pyramid
+bottoms out in zeroes is a polynomial of degree < n, and Newton's forward
+difference formula gives that polynomial directly:
+
+    f(k) = sum over j of C(k, j) * D^j f(0)
+
+where D^j f(0) is the first entry of the j-th difference row, itself
+sum over i of (-1)^(j-i) * C(j, i) * f(i). C(k, j) is the generalised
+binomial k (k-1) ... (k-j+1) / j!, which is an integer for any integer k, so
+k can be negative (k = -1 is part 2) or huge without building the pyramid
+out that far. With rationals k needn't be an integer at all.
+*/
+
+/// Why a history can't be extrapolated exactly.
+#[derive(Debug, PartialEq)]
+pub enum ExtrapolationError {
+    Empty { line: usize },
+    /// the difference pyramid ran out of rows before any of them was all
+    /// zero, the last row tried being the `degree`-th differences
+    NotPolynomial { line: usize, degree: usize },
+    /// the numeric backend couldn't represent some step of the working
+    Arithmetic { line: usize, reason: String },
+}
+
+impl fmt::Display for ExtrapolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtrapolationError::Empty { line } =>
+                write!(f, "line {line}: no readings"),
+            ExtrapolationError::NotPolynomial { line, degree } =>
+                write!(f, "line {line}: not a polynomial, differences still non-zero at degree {degree}"),
+            ExtrapolationError::Arithmetic { line, reason } =>
+                write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+/// C(k, j) for every j in 0..n.
+fn binomials<A: Arithmetic>(a: &A, k: &A::Value, n: usize) -> Option<Vec<A::Value>> {
+    let mut v = Vec::with_capacity(n);
+    let mut c = a.lift(1);
+    for j in 0..n {
+        if j > 0 {
+            // C(k, j) = C(k, j-1) * (k - j + 1) / j, always divides exactly
+            let factor = a.sub(k, &a.lift(j as i64 - 1))?;
+            c = a.div(&a.mul(&c, &factor)?, j as i64)?;
+        }
+        v.push(c.clone());
+    }
+    Some(v)
+}
+
+/// First entry of each difference row: D^0 f(0), D^1 f(0), ...
+fn leading_diffs<A: Arithmetic>(a: &A, history: &[A::Value]) -> Option<Vec<A::Value>> {
+    let mut diffs = Vec::with_capacity(history.len());
+    for j in 0..history.len() {
+        let row_binomials = binomials(a, &a.lift(j as i64), j + 1)?;
+        let mut d = a.lift(0);
+        for (i, c) in row_binomials.iter().enumerate() {
+            let term = a.mul(c, &history[i])?;
+            d = if (j - i) % 2 == 0 { a.add(&d, &term)? } else { a.sub(&d, &term)? };
+        }
+        diffs.push(d);
+    }
+    Some(diffs)
+}
+
+/// Value of the history's polynomial at index `k`, where the first reading
+/// is index 0. `k = history.len()` is the next reading and `k = -1` the one
+/// before the first.
+///
+/// Only histories where some row of the difference pyramid is all zero have
+/// a meaningful extrapolation, anything else is an error. `line` is only
+/// used to label errors.
+pub fn extrapolate<A: Arithmetic>(
+    a: &A,
+    line: usize,
+    history: &[A::Value],
+    k: &A::Value,
+) -> Result<A::Value, ExtrapolationError> {
+    if history.is_empty() {
+        return Err(ExtrapolationError::Empty { line });
+    }
+    let arithmetic = || ExtrapolationError::Arithmetic { line, reason: a.limitation() };
+    // the single entry in the last row is zero exactly when the degree is
+    // low enough for some row to be all zero
+    let diffs = leading_diffs(a, history).ok_or_else(arithmetic)?;
+    if !a.is_zero(diffs.last().unwrap()) {
+        return Err(ExtrapolationError::NotPolynomial { line, degree: diffs.len() - 1 });
+    }
+    evaluate(a, &diffs, k).ok_or_else(arithmetic)
+}
+
+fn evaluate<A: Arithmetic>(a: &A, diffs: &[A::Value], k: &A::Value) -> Option<A::Value> {
+    let mut acc = a.lift(0);
+    for (c, d) in binomials(a, k, diffs.len())?.iter().zip(diffs.iter()) {
+        acc = a.add(&acc, &a.mul(c, d)?)?;
+    }
+    Some(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+
+    use super::*;
+    use crate::numeric::{Big, Checked, Modular, Rational};
+    use crate::{num_str_to_vec, recurse, recurse_2};
+
+    fn big(history: &[i64]) -> Vec<BigInt> {
+        history.iter().map(|v| BigInt::from(*v)).collect()
+    }
+
+    fn check_fixture(input: &str) {
+        for l in input.lines().filter(|l| !l.trim().is_empty()) {
+            let history = num_str_to_vec(l);
+
+            let mut forward = history.clone();
+            recurse(&Checked, &mut forward).unwrap();
+            let next = BigInt::from(history.len());
+            assert_eq!(extrapolate(&Big, 0, &big(&history), &next).unwrap(), BigInt::from(*forward.last().unwrap()), "{l}");
+
+            let mut backward = history.clone();
+            recurse_2(&Checked, &mut backward).unwrap();
+            assert_eq!(extrapolate(&Big, 0, &big(&history), &BigInt::from(-1)).unwrap(), BigInt::from(*backward.first().unwrap()), "{l}");
+        }
+    }
+
+    #[test]
+    fn matches_recursion_on_test() {
+        check_fixture(include_str!("../test.txt"));
+    }
+
+    #[test]
+    fn matches_recursion_on_p2() {
+        check_fixture(include_str!("../p2.txt"));
+    }
+
+    #[test]
+    fn reproduces_history() {
+        let history = [1, 3, 6, 10, 15, 21];
+        for (k, v) in history.iter().enumerate() {
+            assert_eq!(extrapolate(&Big, 0, &big(&history), &BigInt::from(k)).unwrap(), BigInt::from(*v));
+        }
+    }
+
+    #[test]
+    fn rejects_non_polynomial() {
+        let k = BigInt::from(4);
+        assert_eq!(extrapolate(&Big, 3, &big(&[1, 2, 4, 8]), &k), Err(ExtrapolationError::NotPolynomial { line: 3, degree: 3 }));
+        assert_eq!(extrapolate(&Big, 1, &[], &k), Err(ExtrapolationError::Empty { line: 1 }));
+        assert_eq!(extrapolate(&Big, 1, &big(&[1, 2, 3, 4]), &k), Ok(BigInt::from(5)));
+    }
+
+    #[test]
+    fn far_future() {
+        // 0 3 6 9 ... is 3k, 1 3 6 10 ... is (k+1)(k+2)/2
+        let k = BigInt::from(10u64.pow(12));
+        assert_eq!(extrapolate(&Big, 0, &big(&[0, 3, 6, 9, 12, 15]), &k).unwrap(), &k * 3);
+        let expected = (&k + 1) * (&k + 2) / 2;
+        assert_eq!(extrapolate(&Big, 0, &big(&[1, 3, 6, 10, 15, 21]), &k).unwrap(), expected);
+    }
+
+    #[test]
+    fn checked_reports_overflow() {
+        let history = [1, 3, 6, 10, 15, 21];
+        assert_eq!(extrapolate(&Checked, 0, &history, &6), Ok(28));
+        assert_eq!(extrapolate(&Checked, 2, &history, &i64::MAX),
+            Err(ExtrapolationError::Arithmetic { line: 2, reason: String::from("overflowed i64") }));
+    }
+
+    #[test]
+    fn rational_between_readings() {
+        // (k+1)(k+2)/2 at k = 1/2 is 15/8
+        let history: Vec<BigRational> = [1, 3, 6, 10].iter().map(|v| Rational.lift(*v)).collect();
+        let half = Rational.parse("1/2").unwrap();
+        assert_eq!(extrapolate(&Rational, 0, &history, &half), Ok(BigRational::new(15.into(), 8.into())));
+    }
+
+    #[test]
+    fn modular_agrees_with_big() {
+        let m = Modular::new(1_000_000_007).unwrap();
+        let history = [10, 13, 16, 21, 30, 45];
+        let k = BigInt::from(10u64.pow(15));
+        let exact = extrapolate(&Big, 0, &big(&history), &k).unwrap();
+        let reduced = extrapolate(&m, 0, &history.map(|v| m.lift(v)), &m.parse(&k.to_string()).unwrap());
+        assert_eq!(BigInt::from(reduced.unwrap()), exact % 1_000_000_007);
+
+        // dividing by 5 mod 5 has no answer
+        let small = Modular::new(5).unwrap();
+        assert!(matches!(extrapolate(&small, 0, &history.map(|v| small.lift(v)), &0),
+            Err(ExtrapolationError::Arithmetic { .. })));
+    }
+}
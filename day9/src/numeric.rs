@@ -0,0 +1,258 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+
+/// The number system the extrapolator works in. Operations return None
+/// when the answer can't be represented, and `limitation` says why.
+pub trait Arithmetic {
+    type Value: Clone + std::fmt::Display;
+
+    fn parse(&self, input: &str) -> Option<Self::Value>;
+    fn lift(&self, v: i64) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+    fn sub(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+    /// `a / d` for a small positive `d` that's known to divide `a` exactly
+    /// in the integers.
+    fn div(&self, a: &Self::Value, d: i64) -> Option<Self::Value>;
+    fn is_zero(&self, a: &Self::Value) -> bool;
+    /// For the least-squares fallback, None if the value has no place on
+    /// the number line.
+    fn to_f64(&self, a: &Self::Value) -> Option<f64>;
+    fn limitation(&self) -> String;
+}
+
+/// i64 that reports overflow instead of wrapping.
+pub struct Checked;
+
+impl Arithmetic for Checked {
+    type Value = i64;
+
+    fn parse(&self, input: &str) -> Option<i64> {
+        input.parse().ok()
+    }
+    fn lift(&self, v: i64) -> i64 {
+        v
+    }
+    fn add(&self, a: &i64, b: &i64) -> Option<i64> {
+        a.checked_add(*b)
+    }
+    fn sub(&self, a: &i64, b: &i64) -> Option<i64> {
+        a.checked_sub(*b)
+    }
+    fn mul(&self, a: &i64, b: &i64) -> Option<i64> {
+        a.checked_mul(*b)
+    }
+    fn div(&self, a: &i64, d: i64) -> Option<i64> {
+        a.checked_div(d)
+    }
+    fn is_zero(&self, a: &i64) -> bool {
+        *a == 0
+    }
+    fn to_f64(&self, a: &i64) -> Option<f64> {
+        Some(*a as f64)
+    }
+    fn limitation(&self) -> String {
+        String::from("overflowed i64")
+    }
+}
+
+/// Arbitrary-precision integers, which never fail.
+pub struct Big;
+
+impl Arithmetic for Big {
+    type Value = BigInt;
+
+    fn parse(&self, input: &str) -> Option<BigInt> {
+        input.parse().ok()
+    }
+    fn lift(&self, v: i64) -> BigInt {
+        BigInt::from(v)
+    }
+    fn add(&self, a: &BigInt, b: &BigInt) -> Option<BigInt> {
+        Some(a + b)
+    }
+    fn sub(&self, a: &BigInt, b: &BigInt) -> Option<BigInt> {
+        Some(a - b)
+    }
+    fn mul(&self, a: &BigInt, b: &BigInt) -> Option<BigInt> {
+        Some(a * b)
+    }
+    fn div(&self, a: &BigInt, d: i64) -> Option<BigInt> {
+        Some(a / d)
+    }
+    fn is_zero(&self, a: &BigInt) -> bool {
+        a.is_zero()
+    }
+    fn to_f64(&self, a: &BigInt) -> Option<f64> {
+        a.to_f64()
+    }
+    fn limitation(&self) -> String {
+        String::from("can't happen with big integers")
+    }
+}
+
+/// Exact fractions, so the polynomial can be evaluated between readings,
+/// e.g. at index 1/2 or 2.5.
+pub struct Rational;
+
+impl Arithmetic for Rational {
+    type Value = BigRational;
+
+    fn parse(&self, input: &str) -> Option<BigRational> {
+        // 2.5 is 25/10, anything else is an integer or a/b
+        if let Some((whole, frac)) = input.split_once('.') {
+            let digits: BigInt = format!("{whole}{frac}").parse().ok()?;
+            let scale = BigInt::from(10).pow(frac.len() as u32);
+            return Some(BigRational::new(digits, scale));
+        }
+        input.parse().ok()
+    }
+    fn lift(&self, v: i64) -> BigRational {
+        BigRational::from_integer(BigInt::from(v))
+    }
+    fn add(&self, a: &BigRational, b: &BigRational) -> Option<BigRational> {
+        Some(a + b)
+    }
+    fn sub(&self, a: &BigRational, b: &BigRational) -> Option<BigRational> {
+        Some(a - b)
+    }
+    fn mul(&self, a: &BigRational, b: &BigRational) -> Option<BigRational> {
+        Some(a * b)
+    }
+    fn div(&self, a: &BigRational, d: i64) -> Option<BigRational> {
+        Some(a / BigInt::from(d))
+    }
+    fn is_zero(&self, a: &BigRational) -> bool {
+        a.is_zero()
+    }
+    fn to_f64(&self, a: &BigRational) -> Option<f64> {
+        a.to_f64()
+    }
+    fn limitation(&self) -> String {
+        String::from("can't happen with rationals")
+    }
+}
+
+/// Integers modulo a prime, so answers too big to want in full can still
+/// be checked. A history counts as polynomial if its last difference is
+/// zero mod p, which the real difference might not be.
+pub struct Modular {
+    p: u64,
+}
+
+fn mul_mod(a: u64, b: u64, p: u64) -> u64 {
+    (a as u128 * b as u128 % p as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, p: u64) -> u64 {
+    let mut acc = 1 % p;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul_mod(acc, base, p);
+        }
+        base = mul_mod(base, base, p);
+        exp >>= 1;
+    }
+    acc
+}
+
+/// Miller-Rabin with the first twelve primes as witnesses, which is exact
+/// for every u64.
+fn is_prime(n: u64) -> bool {
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(w) = WITNESSES.iter().find(|w| n.is_multiple_of(**w)) {
+        return n == *w;
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    WITNESSES.iter().all(|a| {
+        let mut x = pow_mod(*a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+impl Modular {
+    pub fn new(p: u64) -> Option<Modular> {
+        if is_prime(p) {
+            Some(Modular { p })
+        } else {
+            None
+        }
+    }
+
+    fn reduce(&self, v: &BigInt) -> u64 {
+        let p = BigInt::from(self.p);
+        ((v % &p + &p) % &p).to_u64().unwrap()
+    }
+}
+
+impl Arithmetic for Modular {
+    type Value = u64;
+
+    fn parse(&self, input: &str) -> Option<u64> {
+        input.parse::<BigInt>().ok().map(|v| self.reduce(&v))
+    }
+    fn lift(&self, v: i64) -> u64 {
+        self.reduce(&BigInt::from(v))
+    }
+    fn add(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some(((*a as u128 + *b as u128) % self.p as u128) as u64)
+    }
+    fn sub(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some(((*a as u128 + self.p as u128 - *b as u128) % self.p as u128) as u64)
+    }
+    fn mul(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some(mul_mod(*a, *b, self.p))
+    }
+    fn div(&self, a: &u64, d: i64) -> Option<u64> {
+        // Fermat's little theorem gives the inverse, as long as there is one
+        let d = self.lift(d);
+        if d == 0 {
+            return None;
+        }
+        Some(mul_mod(*a, pow_mod(d, self.p - 2, self.p), self.p))
+    }
+    fn is_zero(&self, a: &u64) -> bool {
+        *a == 0
+    }
+    fn to_f64(&self, _: &u64) -> Option<f64> {
+        None
+    }
+    fn limitation(&self) -> String {
+        format!("needs more readings than the modulus {} allows", self.p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primes() {
+        let primes: Vec<u64> = (0..30).filter(|n| is_prime(*n)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(3_215_031_751));
+    }
+
+    #[test]
+    fn parses_fractions() {
+        assert_eq!(Rational.parse("2.5"), Some(BigRational::new(5.into(), 2.into())));
+        assert_eq!(Rational.parse("-1/3"), Some(BigRational::new((-1).into(), 3.into())));
+        assert_eq!(Modular::new(7).unwrap().parse("-1"), Some(6));
+    }
+}
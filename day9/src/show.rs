@@ -0,0 +1,159 @@
+use std::fmt::Write as _;
+use std::io::IsTerminal;
+
+use crate::numeric::Checked;
+use crate::{final_step, newton, perform_diffs};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Latex,
+    Markdown,
+}
+
+/// One entry of the table, `extrapolated` for the values the puzzle adds
+/// on either end of each row.
+struct Cell {
+    value: i64,
+    extrapolated: bool,
+}
+
+/// The difference pyramid for `history` down to its first all-zero row, each
+/// row with its backward and forward extrapolations on either end. Histories
+/// that aren't polynomial get the rows they have and no extrapolations.
+fn table(history: &[i64], extrapolate: bool) -> Vec<Vec<Cell>> {
+    let mut rows = vec![history.to_vec()];
+    while !final_step(rows.last().unwrap()) {
+        let next = perform_diffs(rows.last().unwrap());
+        rows.push(next);
+    }
+    // a history that isn't polynomial runs out of differences instead
+    if rows.last().unwrap().is_empty() {
+        rows.pop();
+    }
+
+    // walking back up, each row's new ends come from the row below
+    let mut ends = vec![(0, 0); rows.len()];
+    if extrapolate {
+        for r in (0..rows.len() - 1).rev() {
+            let (back, forward) = ends[r + 1];
+            ends[r] = (rows[r][0] - back, rows[r].last().unwrap() + forward);
+        }
+    }
+
+    rows.iter()
+        .zip(ends.iter())
+        .map(|(row, (back, forward))| {
+            let mut cells: Vec<Cell> = row.iter().map(|v| Cell { value: *v, extrapolated: false }).collect();
+            if extrapolate {
+                cells.insert(0, Cell { value: *back, extrapolated: true });
+                cells.push(Cell { value: *forward, extrapolated: true });
+            }
+            cells
+        })
+        .collect()
+}
+
+/// Staggered like the puzzle text: every row shifted half a cell right of
+/// the one above. Extrapolated values are bold when writing to a terminal.
+fn render_text(rows: &[Vec<Cell>]) -> String {
+    let width = rows.iter().flatten().map(|c| c.value.to_string().len()).max().unwrap_or(1);
+    // an even slot keeps the half-cell shift whole
+    let slot = (width + 2).div_ceil(2) * 2;
+    let bold = std::io::stdout().is_terminal();
+
+    let mut out = String::new();
+    for (r, row) in rows.iter().enumerate() {
+        let mut line = " ".repeat(r * slot / 2);
+        for (idx, c) in row.iter().enumerate() {
+            let pad = if idx == 0 { width } else { slot };
+            let v = format!("{:>pad$}", c.value);
+            if c.extrapolated && bold {
+                let split = v.len() - c.value.to_string().len();
+                write!(line, "{}\x1b[1;32m{}\x1b[0m", &v[..split], &v[split..]).unwrap();
+            } else {
+                line.push_str(&v);
+            }
+        }
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+    out
+}
+
+/// Row `r`, entry `j` goes in column `r + 2j`, so the columns between
+/// entries are left empty to give the same stagger as the text.
+fn staggered(rows: &[Vec<Cell>], mark: fn(&Cell) -> String) -> (usize, Vec<Vec<String>>) {
+    let columns = rows.first().map_or(0, |r| 2 * r.len() - 1);
+    let grid = rows.iter()
+        .enumerate()
+        .map(|(r, row)| {
+            let mut line = vec![String::new(); columns];
+            for (j, c) in row.iter().enumerate() {
+                line[r + 2 * j] = mark(c);
+            }
+            line
+        })
+        .collect();
+    (columns, grid)
+}
+
+fn render_latex(rows: &[Vec<Cell>]) -> String {
+    let (columns, grid) = staggered(rows, |c| {
+        if c.extrapolated { format!("\\mathbf{{{}}}", c.value) } else { c.value.to_string() }
+    });
+    let mut out = String::new();
+    writeln!(out, "\\[").unwrap();
+    writeln!(out, "\\begin{{array}}{{{}}}", "c".repeat(columns)).unwrap();
+    for (r, line) in grid.iter().enumerate() {
+        let end = if r + 1 < grid.len() { " \\\\" } else { "" };
+        writeln!(out, "{}{end}", line.join(" & ")).unwrap();
+    }
+    writeln!(out, "\\end{{array}}").unwrap();
+    writeln!(out, "\\]").unwrap();
+    out
+}
+
+fn render_markdown(rows: &[Vec<Cell>]) -> String {
+    let (columns, grid) = staggered(rows, |c| {
+        if c.extrapolated { format!("**{}**", c.value) } else { c.value.to_string() }
+    });
+    let mut out = String::new();
+    // markdown tables need a header, the first row of the pyramid is it
+    for (r, line) in grid.iter().enumerate() {
+        writeln!(out, "| {} |", line.join(" | ")).unwrap();
+        if r == 0 {
+            writeln!(out, "|{}", "---:|".repeat(columns)).unwrap();
+        }
+    }
+    out
+}
+
+/// Prints the difference table for the history on `line` (counting from 1)
+/// of `input`.
+pub fn show(input: &str, line: usize, format: Format) {
+    let Some(l) = line.checked_sub(1).and_then(|idx| input.lines().nth(idx)) else {
+        eprintln!("error: no line {line}, input has {}", input.lines().count());
+        std::process::exit(1);
+    };
+    let history = crate::num_str_to_vec(l);
+
+    // extrapolate checks the pyramid bottoms out before we try to
+    let polynomial = match newton::extrapolate(&Checked, line, &history, &0) {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        },
+    };
+    if history.is_empty() {
+        return;
+    }
+
+    let rows = table(&history, polynomial);
+    let out = match format {
+        Format::Text => render_text(&rows),
+        Format::Latex => render_latex(&rows),
+        Format::Markdown => render_markdown(&rows),
+    };
+    print!("{out}");
+}